@@ -0,0 +1,218 @@
+use crate::client::profile::DEFAULT_PROFILE;
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Serviço usado no chaveiro do sistema (Secret Service, Keychain, Credential Manager)
+const KEYRING_SERVICE: &str = "rustskill";
+/// Conta do perfil padrão (nome mantido das versões sem perfis)
+const DEFAULT_ACCOUNT: &str = "premium-token";
+
+/// Variável de ambiente com o token (CI), nunca persistida em disco
+pub const TOKEN_ENV: &str = "RUSTSKILL_TOKEN";
+
+/// Arquivo de fallback (0600) quando não há chaveiro disponível
+const CREDENTIALS_FILE: &str = "credentials";
+
+/// Onde o token premium ficou guardado
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenLocation {
+    Keyring,
+    File(PathBuf),
+}
+
+impl fmt::Display for TokenLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenLocation::Keyring => write!(f, "chaveiro do sistema"),
+            TokenLocation::File(path) => write!(f, "{} (permissão 0600)", path.display()),
+        }
+    }
+}
+
+/// De onde veio o token usado nesta execução
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenSource {
+    /// `--token-file <arquivo>`
+    File(PathBuf),
+    /// Variável `RUSTSKILL_TOKEN`
+    Env,
+    /// Token guardado pelo `login`
+    Stored(TokenLocation),
+}
+
+impl fmt::Display for TokenSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenSource::File(path) => write!(f, "--token-file {}", path.display()),
+            TokenSource::Env => write!(f, "variável {}", TOKEN_ENV),
+            TokenSource::Stored(location) => write!(f, "{}", location),
+        }
+    }
+}
+
+/// Conta no cofre para o token da API de um perfil ou para a credencial de um host
+pub fn account(profile: &str, host: Option<&str>) -> String {
+    match host {
+        None if profile == DEFAULT_PROFILE => DEFAULT_ACCOUNT.to_string(),
+        None => format!("profile:{}", profile),
+        Some(host) => format!("profile:{}@{}", profile, host.to_lowercase()),
+    }
+}
+
+/// Conta do refresh token que acompanha um token obtido via `login --device`
+pub fn refresh_account(account: &str) -> String {
+    format!("{}#refresh", account)
+}
+
+/// Resolve o token por precedência: `--token-file`, `RUSTSKILL_TOKEN` e, por fim, o cofre
+pub fn resolve_token(
+    account: &str,
+    token_file: Option<&Path>,
+) -> anyhow::Result<Option<(String, TokenSource)>> {
+    if let Some(path) = token_file {
+        let token = read_token_file(path)?;
+        return Ok(Some((token, TokenSource::File(path.to_path_buf()))));
+    }
+
+    if let Ok(token) = std::env::var(TOKEN_ENV) {
+        let token = token.trim().to_string();
+        if !token.is_empty() {
+            return Ok(Some((token, TokenSource::Env)));
+        }
+    }
+
+    Ok(locate_token(account)?.map(|(token, location)| (token, TokenSource::Stored(location))))
+}
+
+/// Lê um token de arquivo, ignorando espaços e quebras de linha nas pontas
+pub fn read_token_file(path: &Path) -> anyhow::Result<String> {
+    let content = fs::read_to_string(path).map_err(|e| {
+        anyhow::anyhow!(
+            "❌ Não foi possível ler o token em {}: {}",
+            path.display(),
+            e
+        )
+    })?;
+    non_empty(content, &path.display().to_string())
+}
+
+/// Lê o token da entrada padrão (`echo $TOKEN | rustskill login --with-token`)
+pub fn read_token_stdin() -> anyhow::Result<String> {
+    let content = std::io::read_to_string(std::io::stdin())?;
+    non_empty(content, "stdin")
+}
+
+fn non_empty(content: String, origin: &str) -> anyhow::Result<String> {
+    let token = content.trim().to_string();
+    if token.is_empty() {
+        anyhow::bail!("❌ Nenhum token encontrado em {}.", origin);
+    }
+    Ok(token)
+}
+
+fn keyring_entry(account: &str) -> keyring::Result<keyring::Entry> {
+    keyring::Entry::new(KEYRING_SERVICE, account)
+}
+
+/// Caminho do fallback, ao lado do arquivo de configuração do confy
+fn credentials_path(account: &str) -> anyhow::Result<PathBuf> {
+    let config = confy::get_configuration_file_path("rustskill", None)?;
+    let dir = config
+        .parent()
+        .ok_or_else(|| anyhow::anyhow!("Diretório de configuração inválido."))?;
+
+    if account == DEFAULT_ACCOUNT {
+        return Ok(dir.join(CREDENTIALS_FILE));
+    }
+    let safe: String = account
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    Ok(dir.join(format!("{}-{}", CREDENTIALS_FILE, safe)))
+}
+
+/// Guarda o token no chaveiro do sistema ou, sem chaveiro, em arquivo 0600
+pub fn store_token(account: &str, token: &str) -> anyhow::Result<TokenLocation> {
+    if keyring_entry(account)
+        .and_then(|entry| entry.set_password(token))
+        .is_ok()
+    {
+        // Não deixa cópia antiga no fallback depois de ir para o chaveiro
+        if let Ok(path) = credentials_path(account) {
+            let _ = fs::remove_file(path);
+        }
+        return Ok(TokenLocation::Keyring);
+    }
+
+    let path = credentials_path(account)?;
+    write_private(&path, token)?;
+    Ok(TokenLocation::File(path))
+}
+
+/// Lê o token guardado: primeiro o chaveiro, depois o arquivo de fallback
+pub fn locate_token(account: &str) -> anyhow::Result<Option<(String, TokenLocation)>> {
+    if let Ok(token) = keyring_entry(account).and_then(|entry| entry.get_password()) {
+        return Ok(Some((token, TokenLocation::Keyring)));
+    }
+
+    let path = credentials_path(account)?;
+    match fs::read_to_string(&path) {
+        Ok(token) => {
+            let token = token.trim().to_string();
+            Ok((!token.is_empty()).then_some((token, TokenLocation::File(path))))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Remove o token do chaveiro e do arquivo de fallback; `true` se havia algo guardado
+pub fn delete_token(account: &str) -> anyhow::Result<bool> {
+    let from_keyring = keyring_entry(account)
+        .and_then(|entry| entry.delete_credential())
+        .is_ok();
+
+    let from_file = match fs::remove_file(credentials_path(account)?) {
+        Ok(()) => true,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => false,
+        Err(e) => return Err(e.into()),
+    };
+
+    Ok(from_keyring || from_file)
+}
+
+/// Grava o arquivo legível apenas pelo dono (0600 em sistemas Unix)
+fn write_private(path: &PathBuf, content: &str) -> anyhow::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        let mut file = options.open(path)?;
+        // O modo do open só vale na criação; corrige arquivos pré-existentes
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        file.write_all(content.as_bytes())?;
+    }
+
+    #[cfg(not(unix))]
+    {
+        let mut file = options.open(path)?;
+        file.write_all(content.as_bytes())?;
+    }
+
+    Ok(())
+}
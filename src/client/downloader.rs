@@ -1,3 +1,7 @@
+use crate::client::profile::Session;
+use crate::client::signature::{self, SIGNATURE_SUFFIX};
+use crate::utils::hash::sha256_hex;
+use chrono::{DateTime, Utc};
 use reqwest::header::{AUTHORIZATION, USER_AGENT};
 use serde::{Deserialize, Serialize};
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -5,11 +9,21 @@ use std::time::{SystemTime, UNIX_EPOCH};
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SkillEntry {
     pub id: String,
-    pub name: String, // Adicionado para bater com o seu JSON do Registry
+    /// Nome de exibição
+    pub name: String,
     pub category: String,
     pub url: String,
     pub premium: bool,
     pub triggers: Option<Vec<String>>,
+    /// SHA-256 (hex) esperado do corpo da skill; divergência recusa o download
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    /// Tamanho esperado do corpo da skill, em bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// Registry de onde a entrada veio (define quais chaves validam a skill)
+    #[serde(skip)]
+    pub registry: String,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -17,49 +31,203 @@ pub struct SkillPayload {
     pub name: String,
     pub instruction: String,
     pub file_name: String,
+    /// Assinatura minisign de `instruction` (API premium)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// Arquivos em que o Cursor aplica a regra (padrão: todos)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub globs: Option<Vec<String>>,
+}
+
+/// Conta dona do token, como devolvida por `/auth/validate`
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct AccountInfo {
+    #[serde(default)]
+    pub identity: Option<String>,
+    #[serde(default)]
+    pub plan: Option<String>,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
 }
 
-const BASE_REGISTRY_URL: &str =
+impl AccountInfo {
+    pub fn is_expired(&self) -> bool {
+        self.expires_at.is_some_and(|exp| exp <= Utc::now())
+    }
+}
+
+/// Versão do formato do registry.json entendida por este cliente
+pub const REGISTRY_SCHEMA_VERSION: u64 = 1;
+/// JSON Schema publicado do registry.json (`rustskill schema registry`)
+pub const REGISTRY_SCHEMA: &str = include_str!("../../schema/registry.v1.json");
+/// JSON Schema publicado da resposta da API premium (`rustskill schema payload`)
+pub const PAYLOAD_SCHEMA: &str = include_str!("../../schema/skill-payload.v1.json");
+
+pub const BASE_REGISTRY_URL: &str =
     "https://raw.githubusercontent.com/cleitonaugusto/rustskill-registry/main/registry.json";
-const API_BASE_URL: &str = "http://api.rustskill.com:8080/v1";
+pub const API_BASE_URL: &str = "https://api.rustskill.com/v1";
 
-pub async fn validate_token(token: &str) -> anyhow::Result<bool> {
+/// Anexa o `Authorization` apenas quando o perfil tem credencial para o host de `url`
+pub(crate) fn authorized(
+    request: reqwest::RequestBuilder,
+    session: &Session,
+    url: &str,
+) -> anyhow::Result<reqwest::RequestBuilder> {
+    match session.token_for(url) {
+        Some(token) => {
+            session.ensure_secure(url)?;
+            Ok(request.header(AUTHORIZATION, format!("Bearer {}", token)))
+        }
+        None => Ok(request),
+    }
+}
+
+/// Valida o token na API do perfil; `None` se ele foi recusado
+pub async fn validate_token(session: &Session, token: &str) -> anyhow::Result<Option<AccountInfo>> {
     let client = reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(5))
         .build()?;
 
+    session.ensure_secure(&session.api_base_url)?;
     let response = client
-        .get(format!("{}/auth/validate", API_BASE_URL))
+        .get(format!("{}/auth/validate", session.api_base_url))
         .header(AUTHORIZATION, format!("Bearer {}", token))
         .header(USER_AGENT, "rustskill-cli")
         .send()
         .await?;
 
-    Ok(response.status().is_success())
+    if !response.status().is_success() {
+        return Ok(None);
+    }
+
+    // Servidores antigos respondem só o status; sem corpo, a conta fica anônima
+    let body = response.text().await?;
+    Ok(Some(serde_json::from_str(&body).unwrap_or_default()))
 }
 
-pub async fn fetch_registry() -> anyhow::Result<Vec<SkillEntry>> {
+/// Lista de skills de um registry.json: `{"schema_version": 1, "skills": [...]}`
+/// ou o formato legado (a lista pura, equivalente à versão 1)
+pub fn parse_registry(body: &[u8], origin: &str) -> anyhow::Result<Vec<SkillEntry>> {
+    let value: serde_json::Value = serde_json::from_slice(body)
+        .map_err(|e| anyhow::anyhow!("❌ O catálogo {} não é um JSON válido: {}", origin, e))?;
+
+    let skills = match value {
+        serde_json::Value::Array(_) => value,
+        serde_json::Value::Object(mut document) => {
+            let version = document
+                .get("schema_version")
+                .and_then(serde_json::Value::as_u64)
+                .ok_or_else(|| {
+                    anyhow::anyhow!("❌ O catálogo {} não declara schema_version.", origin)
+                })?;
+            if version == 0 || version > REGISTRY_SCHEMA_VERSION {
+                anyhow::bail!(
+                    "❌ O catálogo {} usa schema_version {}, mas esta versão do rustskill entende até a {}. Atualize com: rustskill upgrade",
+                    origin,
+                    version,
+                    REGISTRY_SCHEMA_VERSION
+                );
+            }
+            document.remove("skills").ok_or_else(|| {
+                anyhow::anyhow!("❌ O catálogo {} não tem a lista 'skills'.", origin)
+            })?
+        }
+        _ => anyhow::bail!("❌ O catálogo {} tem um formato desconhecido.", origin),
+    };
+
+    serde_json::from_value(skills).map_err(|e| {
+        anyhow::anyhow!(
+            "❌ Entrada inválida no catálogo {}: {}. Rode `rustskill lint` no registry.json para detalhes.",
+            origin,
+            e
+        )
+    })
+}
+
+/// Junta os registries do perfil; em IDs repetidos vale o primeiro registry da lista
+pub async fn fetch_registry(session: &Session) -> anyhow::Result<Vec<SkillEntry>> {
+    let client = reqwest::Client::builder()
+        .user_agent("rustskill-cli")
+        .build()?;
     let ts = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
 
-    let url_with_cache_bust = format!("{}?t={}", BASE_REGISTRY_URL, ts);
-    let response = reqwest::get(&url_with_cache_bust).await?;
+    let mut merged: Vec<SkillEntry> = Vec::new();
+    for registry_url in &session.registries {
+        let separator = if registry_url.contains('?') { "&" } else { "?" };
+        let url_with_cache_bust = format!("{}{}t={}", registry_url, separator, ts);
+        let response = authorized(client.get(&url_with_cache_bust), session, registry_url)?
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "❌ Falha ao acessar o Catálogo {} (Status: {}).",
+                registry_url,
+                response.status()
+            );
+        }
+
+        let body = response.bytes().await?;
+        let keys = session.keys_for(registry_url);
+        if !keys.is_empty() {
+            let sig = fetch_signature(&client, session, registry_url).await?;
+            signature::verify(&body, &sig, keys, registry_url)?;
+        }
+
+        let base = reqwest::Url::parse(registry_url)?;
+        for mut entry in parse_registry(&body, registry_url)? {
+            if !merged.iter().any(|s| s.id == entry.id) {
+                // URLs relativas são resolvidas a partir do próprio registry.json
+                entry.url = base.join(&entry.url)?.to_string();
+                entry.registry = registry_url.clone();
+                merged.push(entry);
+            }
+        }
+    }
+
+    Ok(merged)
+}
+
+/// Confere se `url` responde com sucesso (usado pelo `lint`)
+pub async fn probe(session: &Session, url: &str) -> anyhow::Result<()> {
+    let client = reqwest::Client::builder()
+        .user_agent("rustskill-cli")
+        .timeout(std::time::Duration::from_secs(10))
+        .build()?;
+    let response = authorized(client.get(url), session, url)?.send().await?;
+    if !response.status().is_success() {
+        anyhow::bail!("Status {}", response.status());
+    }
+    Ok(())
+}
+
+/// Baixa `<url>.minisig`; sem assinatura publicada, o recurso é recusado
+pub(crate) async fn fetch_signature(
+    client: &reqwest::Client,
+    session: &Session,
+    url: &str,
+) -> anyhow::Result<String> {
+    let sig_url = format!("{}{}", url, SIGNATURE_SUFFIX);
+    let response = authorized(client.get(&sig_url), session, &sig_url)?
+        .send()
+        .await?;
 
     if !response.status().is_success() {
         anyhow::bail!(
-            "❌ Falha ao acessar o Catálogo Global (Status: {}).",
+            "❌ Assinatura obrigatória ausente em {} (Status: {}).",
+            sig_url,
             response.status()
         );
     }
-
-    Ok(response.json::<Vec<SkillEntry>>().await?)
+    Ok(response.text().await?)
 }
 
-pub async fn fetch_skill(input: &str, token: Option<String>) -> anyhow::Result<SkillPayload> {
+pub async fn fetch_skill(session: &Session, input: &str) -> anyhow::Result<SkillPayload> {
     let client = reqwest::Client::builder()
         .user_agent("rustskill-cli")
         .build()?;
 
-    let registry = fetch_registry().await?;
+    let registry = fetch_registry(session).await?;
     let entry = registry
         .iter()
         .find(|s| s.id == input || s.url == input)
@@ -69,11 +237,17 @@ pub async fn fetch_skill(input: &str, token: Option<String>) -> anyhow::Result<S
 
     // 1. Fazemos a requisição baseada no tipo (Premium vs Público)
     let response = if entry.premium {
-        let tk =
-            token.ok_or_else(|| anyhow::anyhow!("Esta skill exige um Token Premium ativo."))?;
+        let tk = session
+            .token
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("Esta skill exige um Token Premium ativo."))?;
+        session.ensure_secure(&session.api_base_url)?;
 
         client
-            .get(format!("{}/skills/content/{}", API_BASE_URL, entry.id))
+            .get(format!(
+                "{}/skills/content/{}",
+                session.api_base_url, entry.id
+            ))
             .header(AUTHORIZATION, format!("Bearer {}", tk))
             .query(&[("t", ts.to_string())])
             .send()
@@ -81,7 +255,9 @@ pub async fn fetch_skill(input: &str, token: Option<String>) -> anyhow::Result<S
     } else {
         let separator = if entry.url.contains('?') { "&" } else { "?" };
         let skill_url = format!("{}{}t={}", entry.url, separator, ts);
-        client.get(&skill_url).send().await?
+        authorized(client.get(&skill_url), session, &entry.url)?
+            .send()
+            .await?
     };
 
     if !response.status().is_success() {
@@ -89,17 +265,291 @@ pub async fn fetch_skill(input: &str, token: Option<String>) -> anyhow::Result<S
     }
 
     // Tratamento de tipos de resposta diferentes
-    if entry.premium {
+    let payload = if entry.premium {
         // API Premium devolve JSON estruturado
-        Ok(response.json::<SkillPayload>().await?)
+        response.json::<SkillPayload>().await?
     } else {
-        // GitHub Raw devolve TEXTO PURO (.mdc)
-        let raw_text = response.text().await?;
+        // GitHub Raw devolve TEXTO PURO (.mdc); os bytes crus são os que o hash cobre
+        let raw_text = String::from_utf8(response.bytes().await?.to_vec()).map_err(|_| {
+            anyhow::anyhow!("❌ O conteúdo da skill '{}' não é UTF-8 válido.", entry.id)
+        })?;
+        let signature = if session.keys_for(&entry.registry).is_empty() {
+            None
+        } else {
+            Some(fetch_signature(&client, session, &entry.url).await?)
+        };
 
-        Ok(SkillPayload {
+        SkillPayload {
             name: entry.name.clone(),
             instruction: raw_text,
             file_name: entry.id.replace("/", "-") + ".mdc",
-        })
+            signature,
+            globs: None,
+        }
+    };
+
+    // 2. Hash e tamanho fixados no registry protegem contra CDN/URL adulterados
+    check_integrity(entry, payload.instruction.as_bytes())?;
+
+    // 3. Registry com chaves confiáveis: nada é devolvido (e instalado) sem assinatura válida
+    require_signature(session.keys_for(&entry.registry), &entry.id, &payload)?;
+
+    Ok(payload)
+}
+
+/// Com chaves configuradas, a skill precisa de assinatura válida; sem chaves, nada é exigido
+fn require_signature(keys: &[String], id: &str, payload: &SkillPayload) -> anyhow::Result<()> {
+    if keys.is_empty() {
+        return Ok(());
+    }
+    let sig = payload.signature.as_deref().ok_or_else(|| {
+        anyhow::anyhow!("❌ A skill '{}' não veio assinada. Nada foi instalado.", id)
+    })?;
+    signature::verify(payload.instruction.as_bytes(), sig, keys, id)
+}
+
+/// Compara o corpo baixado com o `size` e o `sha256` declarados na entrada do registry
+pub fn check_integrity(entry: &SkillEntry, body: &[u8]) -> anyhow::Result<()> {
+    if let Some(size) = entry.size {
+        if body.len() as u64 != size {
+            anyhow::bail!(
+                "❌ Integridade violada em '{}': esperado {} bytes, recebido {}. Nada foi instalado.",
+                entry.id,
+                size,
+                body.len()
+            );
+        }
+    }
+
+    if let Some(expected) = &entry.sha256 {
+        let actual = sha256_hex(body);
+        if !actual.eq_ignore_ascii_case(expected.trim()) {
+            anyhow::bail!(
+                "❌ Integridade violada em '{}': sha256 esperado {}, recebido {}. Nada foi instalado.",
+                entry.id,
+                expected,
+                actual
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::de::DeserializeOwned;
+    use serde_json::{json, Value};
+    use std::collections::BTreeSet;
+
+    const ENTRY: &str = r#"{"id":"rust/clean-code","name":"Clean Code","category":"Rust","url":"skills/clean-code.mdc","premium":false,"triggers":["serde"]}"#;
+
+    fn full_entry() -> SkillEntry {
+        SkillEntry {
+            id: "rust/clean-code".to_string(),
+            name: "Clean Code".to_string(),
+            category: "Rust".to_string(),
+            url: "skills/clean-code.mdc".to_string(),
+            premium: false,
+            triggers: Some(vec!["serde".to_string()]),
+            sha256: Some("0".repeat(64)),
+            size: Some(1),
+            registry: String::new(),
+        }
+    }
+
+    fn full_payload() -> SkillPayload {
+        SkillPayload {
+            name: "Clean Code".to_string(),
+            instruction: "Seja claro.".to_string(),
+            file_name: "clean-code.mdc".to_string(),
+            signature: Some("sig".to_string()),
+            globs: Some(vec!["**/*.rs".to_string()]),
+        }
+    }
+
+    fn keys(value: &Value) -> BTreeSet<String> {
+        value.as_object().unwrap().keys().cloned().collect()
+    }
+
+    fn strings(value: &Value) -> BTreeSet<String> {
+        value
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_str().unwrap().to_string())
+            .collect()
+    }
+
+    /// Campos sem os quais a desserialização falha
+    fn required_fields<T: DeserializeOwned>(full: &Value) -> BTreeSet<String> {
+        keys(full)
+            .into_iter()
+            .filter(|key| {
+                let mut partial = full.clone();
+                partial.as_object_mut().unwrap().remove(key);
+                serde_json::from_value::<T>(partial).is_err()
+            })
+            .collect()
+    }
+
+    /// O schema publicado precisa acompanhar os campos serde dos tipos
+    fn assert_schema_matches<T: DeserializeOwned>(schema: &Value, full: Value) {
+        assert_eq!(keys(&schema["properties"]), keys(&full));
+        assert_eq!(strings(&schema["required"]), required_fields::<T>(&full));
+    }
+
+    #[test]
+    fn registry_schema_matches_skill_entry() {
+        let schema: Value = serde_json::from_str(REGISTRY_SCHEMA).unwrap();
+        let full = serde_json::to_value(full_entry()).unwrap();
+        assert_schema_matches::<SkillEntry>(&schema["$defs"]["SkillEntry"], full);
+        assert_eq!(
+            schema["properties"]["schema_version"]["const"],
+            json!(REGISTRY_SCHEMA_VERSION)
+        );
+    }
+
+    #[test]
+    fn payload_schema_matches_skill_payload() {
+        let schema: Value = serde_json::from_str(PAYLOAD_SCHEMA).unwrap();
+        let full = serde_json::to_value(full_payload()).unwrap();
+        assert_schema_matches::<SkillPayload>(&schema, full);
+    }
+
+    #[test]
+    fn parses_registry_envelope() {
+        let body = format!(r#"{{"schema_version":1,"skills":[{}]}}"#, ENTRY);
+        let skills = parse_registry(body.as_bytes(), "test").unwrap();
+        assert_eq!(skills.len(), 1);
+        assert_eq!(skills[0].id, "rust/clean-code");
+        assert_eq!(skills[0].triggers, Some(vec!["serde".to_string()]));
+    }
+
+    #[test]
+    fn parses_legacy_registry_list() {
+        let body = format!("[{}]", ENTRY);
+        let skills = parse_registry(body.as_bytes(), "test").unwrap();
+        assert_eq!(skills[0].name, "Clean Code");
+    }
+
+    #[test]
+    fn rejects_unsupported_schema_versions() {
+        for version in [0, REGISTRY_SCHEMA_VERSION + 1] {
+            let body = format!(r#"{{"schema_version":{},"skills":[]}}"#, version);
+            let err = parse_registry(body.as_bytes(), "test").unwrap_err();
+            assert!(
+                err.to_string()
+                    .contains(&format!("schema_version {}", version)),
+                "{}",
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_registries() {
+        for body in [
+            r#"{"skills":[]}"#,
+            r#"{"schema_version":"1","skills":[]}"#,
+            r#"{"schema_version":1}"#,
+            r#"{"schema_version":1,"skills":[{"id":"rust/x"}]}"#,
+            r#""registry""#,
+            "not json",
+        ] {
+            assert!(
+                parse_registry(body.as_bytes(), "test").is_err(),
+                "aceitou {}",
+                body
+            );
+        }
+    }
+
+    fn pinned(sha256: Option<String>, size: Option<u64>) -> SkillEntry {
+        SkillEntry {
+            sha256,
+            size,
+            ..full_entry()
+        }
+    }
+
+    const BODY: &[u8] = b"Use Result.\n";
+
+    #[test]
+    fn integrity_accepts_matching_or_missing_pins() {
+        let digest = sha256_hex(BODY);
+        assert!(check_integrity(&pinned(None, None), BODY).is_ok());
+        assert!(check_integrity(&pinned(Some(digest.clone()), Some(12)), BODY).is_ok());
+        assert!(check_integrity(&pinned(Some(digest.to_uppercase()), None), BODY).is_ok());
+        assert!(check_integrity(&pinned(Some(format!("  {}\n", digest)), None), BODY).is_ok());
+    }
+
+    #[test]
+    fn integrity_rejects_size_mismatch() {
+        let err = check_integrity(&pinned(None, Some(11)), BODY).unwrap_err();
+        assert!(err.to_string().contains("esperado 11 bytes"), "{}", err);
+    }
+
+    #[test]
+    fn integrity_rejects_sha256_mismatch() {
+        let other = sha256_hex(b"Use panic!.\n");
+        let err = check_integrity(&pinned(Some(other), Some(12)), BODY).unwrap_err();
+        assert!(err.to_string().contains("sha256"), "{}", err);
+        assert!(check_integrity(&pinned(Some("abc".to_string()), None), BODY).is_err());
+    }
+
+    /// Par de chaves minisign de teste e a assinatura de `BODY` com a primeira
+    const TEST_KEY: &str = "RWQBAgMEBQYHCFxCHN7ho8rKqyw8lNHVsicelxayejn3wYjev49a25pQ";
+    const OTHER_KEY: &str = "RWQJCgsMDQ4PEOIJaHXCaKCZPyD6S2bhX/ZR6eVc0sCN+7JOH0B5wUW6";
+    const BODY_SIG: &str = "untrusted comment: test\nRUQBAgMEBQYHCMIEBGsweBBDa5to7I6F34CMYxMC16CSDuSZFx6DNk2rN0UAQgZxHhfl0XOFIzXegnm3mPUwS35/E24b/RjoXwA=\ntrusted comment: timestamp:0\ngYwi8qqOevoHXQ42Oph3ki18z7SZmmXWKWbIgmcWFU/dviPQEO/Ear4TyzjU3KtqJvE0m3/IXAlijn+q7GfcCQ==\n";
+
+    fn signed(instruction: &[u8], sig: Option<&str>) -> SkillPayload {
+        SkillPayload {
+            instruction: String::from_utf8(instruction.to_vec()).unwrap(),
+            signature: sig.map(str::to_string),
+            ..full_payload()
+        }
+    }
+
+    fn trusted(list: &[&str]) -> Vec<String> {
+        list.iter().map(|k| k.to_string()).collect()
+    }
+
+    #[test]
+    fn signature_accepts_valid_pair() {
+        assert!(signature::verify(BODY, BODY_SIG, &trusted(&[TEST_KEY]), "rust/x").is_ok());
+        // Basta uma das chaves confiáveis validar
+        assert!(
+            signature::verify(BODY, BODY_SIG, &trusted(&[OTHER_KEY, TEST_KEY]), "rust/x").is_ok()
+        );
+        assert!(require_signature(
+            &trusted(&[TEST_KEY]),
+            "rust/x",
+            &signed(BODY, Some(BODY_SIG))
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn signature_rejects_tampered_body() {
+        let err = signature::verify(b"Use panic!.\n", BODY_SIG, &trusted(&[TEST_KEY]), "rust/x")
+            .unwrap_err();
+        assert!(err.to_string().contains("não confere"), "{}", err);
+    }
+
+    #[test]
+    fn signature_rejects_unknown_key() {
+        assert!(signature::verify(BODY, BODY_SIG, &trusted(&[OTHER_KEY]), "rust/x").is_err());
+        assert!(signature::verify(BODY, BODY_SIG, &trusted(&["não é chave"]), "rust/x").is_err());
+        assert!(signature::verify(BODY, "lixo", &trusted(&[TEST_KEY]), "rust/x").is_err());
+    }
+
+    #[test]
+    fn unsigned_payload_fails_closed_when_keys_are_configured() {
+        let err =
+            require_signature(&trusted(&[TEST_KEY]), "rust/x", &signed(BODY, None)).unwrap_err();
+        assert!(err.to_string().contains("não veio assinada"), "{}", err);
+        assert!(require_signature(&[], "rust/x", &signed(BODY, None)).is_ok());
     }
 }
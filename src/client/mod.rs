@@ -1 +1,7 @@
+pub mod credentials;
 pub mod downloader;
+pub mod oauth;
+pub mod profile;
+pub mod signature;
+pub mod source;
+pub mod updater;
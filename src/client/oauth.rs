@@ -0,0 +1,436 @@
+use crate::client::credentials::{self, TokenSource};
+use crate::client::downloader::{self, AccountInfo};
+use crate::client::profile::Session;
+use reqwest::header::USER_AGENT;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+
+/// Cliente público registrado na API para o fluxo de dispositivo (RFC 8628)
+pub const CLIENT_ID: &str = "rustskill-cli";
+const DEVICE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+/// Acréscimo ao intervalo a cada `slow_down` (RFC 8628 §3.5)
+const SLOW_DOWN_STEP: Duration = Duration::from_secs(5);
+
+/// Resposta de `/oauth/device/code`: o código que o usuário digita no navegador
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeviceAuthorization {
+    pub device_code: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    #[serde(default)]
+    pub verification_uri_complete: Option<String>,
+    pub expires_in: u64,
+    #[serde(default = "default_interval")]
+    pub interval: u64,
+}
+
+fn default_interval() -> u64 {
+    5
+}
+
+/// Tokens emitidos por `/oauth/token`
+#[derive(Debug, Clone, Deserialize)]
+pub struct TokenSet {
+    pub access_token: String,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OAuthError {
+    error: String,
+    #[serde(default)]
+    error_description: Option<String>,
+}
+
+/// Lê o corpo como JSON; HTML de proxy ou corpo vazio viram um erro com o status HTTP
+async fn read_json<T: DeserializeOwned>(response: reqwest::Response) -> anyhow::Result<T> {
+    let status = response.status();
+    let body = response.text().await?;
+    serde_json::from_str(&body).map_err(|_| {
+        anyhow::anyhow!(
+            "❌ Resposta inesperada do servidor de login (Status: {}).",
+            status
+        )
+    })
+}
+
+fn client() -> anyhow::Result<reqwest::Client> {
+    Ok(reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()?)
+}
+
+/// Inicia o fluxo: pede à API um par device_code/user_code
+pub async fn start_device_flow(session: &Session) -> anyhow::Result<DeviceAuthorization> {
+    session.ensure_secure(&session.api_base_url)?;
+    let response = client()?
+        .post(format!("{}/oauth/device/code", session.api_base_url))
+        .header(USER_AGENT, "rustskill-cli")
+        .form(&[("client_id", CLIENT_ID)])
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        anyhow::bail!(
+            "❌ A API recusou o login por dispositivo (Status: {}).",
+            response.status()
+        );
+    }
+
+    read_json(response).await
+}
+
+/// Consulta `/oauth/token` no intervalo pedido pelo servidor até o usuário autorizar
+pub async fn poll_for_token(
+    session: &Session,
+    auth: &DeviceAuthorization,
+) -> anyhow::Result<TokenSet> {
+    poll_with(session, auth, SLOW_DOWN_STEP).await
+}
+
+/// Núcleo do `poll_for_token`, com o acréscimo do `slow_down` injetado
+async fn poll_with(
+    session: &Session,
+    auth: &DeviceAuthorization,
+    slow_down_step: Duration,
+) -> anyhow::Result<TokenSet> {
+    session.ensure_secure(&session.api_base_url)?;
+    let client = client()?;
+    let deadline = Instant::now() + Duration::from_secs(auth.expires_in);
+    let mut interval = Duration::from_secs(auth.interval);
+
+    loop {
+        if Instant::now() >= deadline {
+            anyhow::bail!("❌ O código expirou antes da autorização. Rode o login novamente.");
+        }
+        tokio::time::sleep(interval).await;
+
+        let response = client
+            .post(format!("{}/oauth/token", session.api_base_url))
+            .header(USER_AGENT, "rustskill-cli")
+            .form(&[
+                ("grant_type", DEVICE_GRANT),
+                ("device_code", auth.device_code.as_str()),
+                ("client_id", CLIENT_ID),
+            ])
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            return read_json(response).await;
+        }
+
+        let error: OAuthError = read_json(response).await?;
+        match error.error.as_str() {
+            "authorization_pending" => {}
+            // O servidor pediu para desacelerar: +5s em produção a cada aviso (RFC 8628 §3.5)
+            "slow_down" => interval += slow_down_step,
+            "access_denied" => anyhow::bail!("❌ Autorização negada no navegador."),
+            "expired_token" => {
+                anyhow::bail!("❌ O código expirou antes da autorização. Rode o login novamente.")
+            }
+            other => anyhow::bail!(
+                "❌ Falha no login por dispositivo: {} {}",
+                other,
+                error.error_description.unwrap_or_default()
+            ),
+        }
+    }
+}
+
+/// Troca o refresh token por um novo par de tokens
+pub async fn refresh(session: &Session, refresh_token: &str) -> anyhow::Result<TokenSet> {
+    session.ensure_secure(&session.api_base_url)?;
+    let response = client()?
+        .post(format!("{}/oauth/token", session.api_base_url))
+        .header(USER_AGENT, "rustskill-cli")
+        .form(&[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+            ("client_id", CLIENT_ID),
+        ])
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        anyhow::bail!(
+            "❌ Não foi possível renovar a sessão (Status: {}). Faça login novamente.",
+            response.status()
+        );
+    }
+
+    read_json(response).await
+}
+
+/// Guarda o access token (e o refresh token, se houver) no cofre do perfil
+pub fn store_tokens(
+    profile: &str,
+    tokens: &TokenSet,
+) -> anyhow::Result<credentials::TokenLocation> {
+    let account = credentials::account(profile, None);
+    let location = credentials::store_token(&account, &tokens.access_token)?;
+    if let Some(refresh_token) = &tokens.refresh_token {
+        credentials::store_token(&credentials::refresh_account(&account), refresh_token)?;
+    }
+    Ok(location)
+}
+
+/// Valida o token da sessão e, se a API o der como expirado, renova com o refresh token
+pub async fn validate_with_refresh(session: &mut Session) -> anyhow::Result<Option<AccountInfo>> {
+    validate_with(
+        session,
+        |profile| {
+            let account = credentials::refresh_account(&credentials::account(profile, None));
+            Ok(credentials::locate_token(&account)?.map(|(token, _)| token))
+        },
+        |profile, tokens| store_tokens(profile, tokens).map(|_| ()),
+    )
+    .await
+}
+
+/// Núcleo do `validate_with_refresh`, com a leitura e a gravação no cofre injetadas
+async fn validate_with(
+    session: &mut Session,
+    load_refresh: impl FnOnce(&str) -> anyhow::Result<Option<String>>,
+    store: impl FnOnce(&str, &TokenSet) -> anyhow::Result<()>,
+) -> anyhow::Result<Option<AccountInfo>> {
+    let Some(token) = session.token.clone() else {
+        return Ok(None);
+    };
+
+    let account = downloader::validate_token(session, &token).await?;
+    if account.as_ref().is_some_and(|a| !a.is_expired()) {
+        return Ok(account);
+    }
+
+    // Tokens de --token-file ou RUSTSKILL_TOKEN não são nossos para renovar
+    if !matches!(session.token_source, Some(TokenSource::Stored(_))) {
+        return Ok(account);
+    }
+
+    let Some(refresh_token) = load_refresh(&session.profile)? else {
+        return Ok(account);
+    };
+
+    let tokens = refresh(session, &refresh_token).await?;
+    store(&session.profile, &tokens)?;
+    session.token = Some(tokens.access_token.clone());
+
+    downloader::validate_token(session, &tokens.access_token).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::credentials::TokenLocation;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Requisição recebida pelo servidor falso
+    #[derive(Debug, Clone)]
+    struct Seen {
+        path: String,
+        body: String,
+        authorization: Option<String>,
+        at: Instant,
+    }
+
+    /// Servidor de autorização falso: responde o roteiro na ordem, uma conexão por resposta
+    async fn mock_server(
+        script: Vec<(&'static str, u16, &'static str)>,
+    ) -> (Session, Arc<Mutex<Vec<Seen>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = seen.clone();
+
+        tokio::spawn(async move {
+            for (expected_path, status, body) in script {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let request = read_request(&mut socket).await;
+                assert_eq!(request.path, expected_path, "requisição fora do roteiro");
+                log.lock().unwrap().push(request);
+
+                let response = format!(
+                    "HTTP/1.1 {} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+                socket.shutdown().await.unwrap();
+            }
+        });
+        (Session::for_tests(&base), seen)
+    }
+
+    async fn read_request(socket: &mut tokio::net::TcpStream) -> Seen {
+        let mut raw = Vec::new();
+        let mut buf = [0u8; 1024];
+        let header_end = loop {
+            let n = socket.read(&mut buf).await.unwrap();
+            raw.extend_from_slice(&buf[..n]);
+            if let Some(pos) = raw.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos + 4;
+            }
+            assert!(n > 0, "conexão fechada no meio do cabeçalho");
+        };
+        let head = String::from_utf8_lossy(&raw[..header_end]).to_string();
+        let header = |name: &str| {
+            head.lines().find_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                key.eq_ignore_ascii_case(name)
+                    .then(|| value.trim().to_string())
+            })
+        };
+        let length: usize = header("content-length").map_or(0, |v| v.parse().unwrap());
+        while raw.len() < header_end + length {
+            let n = socket.read(&mut buf).await.unwrap();
+            raw.extend_from_slice(&buf[..n]);
+        }
+
+        Seen {
+            path: head
+                .split_whitespace()
+                .nth(1)
+                .unwrap_or_default()
+                .to_string(),
+            body: String::from_utf8_lossy(&raw[header_end..header_end + length]).to_string(),
+            authorization: header("authorization"),
+            at: Instant::now(),
+        }
+    }
+
+    const DEVICE_CODE: &str = r#"{"device_code":"dev-1","user_code":"ABCD-EFGH","verification_uri":"https://example.com/device","expires_in":60,"interval":0}"#;
+
+    #[tokio::test]
+    async fn device_flow_waits_slows_down_and_succeeds() {
+        let (session, seen) = mock_server(vec![
+            ("/oauth/device/code", 200, DEVICE_CODE),
+            ("/oauth/token", 400, r#"{"error":"authorization_pending"}"#),
+            ("/oauth/token", 400, r#"{"error":"slow_down"}"#),
+            (
+                "/oauth/token",
+                200,
+                r#"{"access_token":"at-1","refresh_token":"rt-1"}"#,
+            ),
+        ])
+        .await;
+
+        let auth = start_device_flow(&session).await.unwrap();
+        assert_eq!(auth.user_code, "ABCD-EFGH");
+        // Passo curto para a suíte não esperar os 5s de produção
+        let step = Duration::from_millis(50);
+        let tokens = poll_with(&session, &auth, step).await.unwrap();
+        assert_eq!(tokens.access_token, "at-1");
+        assert_eq!(tokens.refresh_token.as_deref(), Some("rt-1"));
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 4);
+        assert!(seen[0].body.contains("client_id=rustskill-cli"));
+        assert!(seen[1].body.contains("device_code=dev-1"));
+        assert!(seen[1]
+            .body
+            .contains("grant_type=urn%3Aietf%3Aparams%3Aoauth%3Agrant-type%3Adevice_code"));
+        // Depois do slow_down o intervalo cresce
+        assert!(seen[3].at - seen[2].at >= step);
+        assert_eq!(SLOW_DOWN_STEP, Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn device_flow_reports_access_denied() {
+        let (session, _) = mock_server(vec![
+            ("/oauth/device/code", 200, DEVICE_CODE),
+            ("/oauth/token", 400, r#"{"error":"authorization_pending"}"#),
+            ("/oauth/token", 400, r#"{"error":"access_denied"}"#),
+        ])
+        .await;
+
+        let auth = start_device_flow(&session).await.unwrap();
+        let err = poll_for_token(&session, &auth).await.unwrap_err();
+        assert!(err.to_string().contains("Autorização negada"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn non_json_error_mentions_status() {
+        let (session, _) = mock_server(vec![
+            ("/oauth/device/code", 200, DEVICE_CODE),
+            ("/oauth/token", 502, "<html>Bad Gateway</html>"),
+        ])
+        .await;
+
+        let auth = start_device_flow(&session).await.unwrap();
+        let err = poll_for_token(&session, &auth).await.unwrap_err();
+        assert!(err.to_string().contains("502"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn refreshes_expired_token() {
+        let (mut session, seen) = mock_server(vec![
+            (
+                "/auth/validate",
+                200,
+                r#"{"identity":"ana","expires_at":"2000-01-01T00:00:00Z"}"#,
+            ),
+            (
+                "/oauth/token",
+                200,
+                r#"{"access_token":"at-2","refresh_token":"rt-2"}"#,
+            ),
+            ("/auth/validate", 200, r#"{"identity":"ana","plan":"pro"}"#),
+        ])
+        .await;
+        session.token = Some("at-1".to_string());
+        session.token_source = Some(TokenSource::Stored(TokenLocation::Keyring));
+
+        let stored = Mutex::new(None);
+        let account = validate_with(
+            &mut session,
+            |_| Ok(Some("rt-1".to_string())),
+            |_, tokens| {
+                *stored.lock().unwrap() = Some(tokens.clone());
+                Ok(())
+            },
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(account.plan.as_deref(), Some("pro"));
+        assert_eq!(session.token.as_deref(), Some("at-2"));
+        let stored = stored.into_inner().unwrap().unwrap();
+        assert_eq!(stored.refresh_token.as_deref(), Some("rt-2"));
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen[0].authorization.as_deref(), Some("Bearer at-1"));
+        assert!(seen[1].body.contains("grant_type=refresh_token"));
+        assert!(seen[1].body.contains("refresh_token=rt-1"));
+        assert_eq!(seen[2].authorization.as_deref(), Some("Bearer at-2"));
+    }
+
+    #[tokio::test]
+    async fn does_not_refresh_tokens_from_env() {
+        let (mut session, seen) = mock_server(vec![(
+            "/auth/validate",
+            200,
+            r#"{"expires_at":"2000-01-01T00:00:00Z"}"#,
+        )])
+        .await;
+        session.token = Some("at-1".to_string());
+        session.token_source = Some(TokenSource::Env);
+
+        let account = validate_with(
+            &mut session,
+            |_| panic!("não deveria ler o cofre"),
+            |_, _| panic!("não deveria gravar no cofre"),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert!(account.is_expired());
+        assert_eq!(seen.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,298 @@
+use crate::client::credentials::{self, TokenSource};
+use crate::client::downloader::{API_BASE_URL, BASE_REGISTRY_URL};
+use crate::client::updater::UpdateConfig;
+use crate::core::guard::GuardConfig;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// Perfil usado quando nenhum `--profile` é informado nem configurado
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Sobrescreve a URL da API do perfil (ex.: servidor de homologação)
+pub const API_URL_ENV: &str = "RUSTSKILL_API_URL";
+
+/// Configuração persistida pelo confy (nunca contém tokens nas versões atuais)
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Config {
+    /// Legado: token em texto puro das versões antigas, migrado para o cofre na inicialização
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Perfil ativo quando `--profile` não é informado
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_profile: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
+    /// Análise de conteúdo das skills antes da instalação
+    #[serde(default)]
+    pub guard: GuardConfig,
+    /// Canal de releases e aviso de nova versão
+    #[serde(default)]
+    pub update: UpdateConfig,
+}
+
+impl Config {
+    pub fn load() -> anyhow::Result<Self> {
+        Ok(confy::load("rustskill", None)?)
+    }
+
+    pub fn store(&self) -> anyhow::Result<()> {
+        Ok(confy::store("rustskill", None, self)?)
+    }
+
+    /// Nome do perfil efetivo: `--profile`, depois o padrão configurado
+    pub fn active_profile(&self, requested: Option<&str>) -> String {
+        requested
+            .or(self.default_profile.as_deref())
+            .unwrap_or(DEFAULT_PROFILE)
+            .to_string()
+    }
+}
+
+/// Um cliente/assinatura: API, registries privados e hosts com credencial própria
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Profile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_base_url: Option<String>,
+    /// URLs de `registry.json`; vazio usa o Catálogo Global
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub registries: Vec<String>,
+    /// Hosts com token próprio guardado no cofre (`login --host`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hosts: Vec<String>,
+    /// URL do registry -> chaves públicas minisign aceitas para ele e suas skills
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub trusted_keys: BTreeMap<String, Vec<String>>,
+}
+
+/// Perfil resolvido para esta execução, com os tokens já carregados do cofre
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub profile: String,
+    pub api_base_url: String,
+    pub registries: Vec<String>,
+    /// Token da API premium do perfil
+    pub token: Option<String>,
+    pub token_source: Option<TokenSource>,
+    /// Host (minúsculo) -> token exclusivo daquele host
+    host_tokens: HashMap<String, String>,
+    /// Registries com assinatura obrigatória e suas chaves confiáveis
+    pub trusted_keys: BTreeMap<String, Vec<String>>,
+    /// Permite credenciais sobre HTTP puro (`--insecure`, apenas para testes locais)
+    pub allow_insecure: bool,
+}
+
+impl Session {
+    /// Monta a sessão do perfil pedido (ou padrão), resolvendo os tokens por precedência
+    pub fn load(requested: Option<&str>, token_file: Option<&Path>) -> anyhow::Result<Self> {
+        let config = Config::load().unwrap_or_default();
+        let name = config.active_profile(requested);
+
+        let profile = match config.profiles.get(&name) {
+            Some(profile) => profile.clone(),
+            None if name == DEFAULT_PROFILE => Profile::default(),
+            None => anyhow::bail!(
+                "❌ Perfil '{}' não existe. Crie com: rustskill profile add {}",
+                name,
+                name
+            ),
+        };
+
+        let (token, token_source) =
+            match credentials::resolve_token(&credentials::account(&name, None), token_file)? {
+                Some((token, source)) => (Some(token), Some(source)),
+                None => (None, None),
+            };
+
+        let mut host_tokens = HashMap::new();
+        for host in &profile.hosts {
+            if let Some((token, _)) =
+                credentials::locate_token(&credentials::account(&name, Some(host)))?
+            {
+                host_tokens.insert(host.to_lowercase(), token);
+            }
+        }
+
+        // Precedência da API: RUSTSKILL_API_URL, perfil e, por fim, o endpoint oficial
+        let api_base_url = std::env::var(API_URL_ENV)
+            .ok()
+            .filter(|url| !url.trim().is_empty())
+            .or_else(|| profile.api_base_url.clone())
+            .unwrap_or_else(|| API_BASE_URL.to_string());
+        Url::parse(&api_base_url)
+            .map_err(|e| anyhow::anyhow!("❌ URL da API inválida '{}': {}", api_base_url, e))?;
+
+        Ok(Self {
+            api_base_url: api_base_url.trim_end_matches('/').to_string(),
+            registries: if profile.registries.is_empty() {
+                vec![BASE_REGISTRY_URL.to_string()]
+            } else {
+                profile.registries.clone()
+            },
+            trusted_keys: profile.trusted_keys.clone(),
+            profile: name,
+            token,
+            token_source,
+            host_tokens,
+            allow_insecure: false,
+        })
+    }
+
+    /// Sessão sem perfil nem cofre, apontando para uma API local
+    #[cfg(test)]
+    pub fn for_tests(api_base_url: &str) -> Self {
+        Self {
+            profile: DEFAULT_PROFILE.to_string(),
+            api_base_url: api_base_url.trim_end_matches('/').to_string(),
+            registries: Vec::new(),
+            token: None,
+            token_source: None,
+            host_tokens: HashMap::new(),
+            trusted_keys: BTreeMap::new(),
+            allow_insecure: true,
+        }
+    }
+
+    /// Recusa enviar credenciais para `url` sem TLS, salvo com `--insecure`
+    pub fn ensure_secure(&self, url: &str) -> anyhow::Result<()> {
+        let scheme = Url::parse(url)
+            .map_err(|e| anyhow::anyhow!("❌ URL inválida '{}': {}", url, e))?
+            .scheme()
+            .to_string();
+
+        if scheme != "https" && !self.allow_insecure {
+            anyhow::bail!(
+                "❌ Recusado: credenciais não são enviadas sem HTTPS ({}). Para testes locais use --insecure.",
+                url
+            );
+        }
+        Ok(())
+    }
+
+    /// Chaves confiáveis do registry; vazio significa que ele não exige assinatura
+    pub fn keys_for(&self, registry_url: &str) -> &[String] {
+        self.trusted_keys
+            .get(registry_url)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Token que pode ser enviado para `url`: a credencial do host, ou o token do
+    /// perfil se `url` for a própria API. Qualquer outro destino não recebe nada.
+    pub fn token_for(&self, url: &str) -> Option<&str> {
+        let target = Url::parse(url).ok()?;
+        let host = target.host_str()?.to_lowercase();
+
+        if let Some(token) = self.host_tokens.get(&host) {
+            return Some(token);
+        }
+
+        let api = Url::parse(&self.api_base_url).ok()?;
+        let same_origin = api.scheme() == target.scheme()
+            && api.host_str().map(str::to_lowercase).as_deref() == Some(host.as_str())
+            && api.port_or_known_default() == target.port_or_known_default();
+        if same_origin {
+            self.token.as_deref()
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::downloader::authorized;
+    use reqwest::header::AUTHORIZATION;
+
+    fn session() -> Session {
+        let mut session = Session::for_tests("https://api.rustskill.com");
+        session.allow_insecure = false;
+        session.token = Some("profile-token".to_string());
+        session
+            .host_tokens
+            .insert("registry.acme.com".to_string(), "acme-token".to_string());
+        session
+    }
+
+    fn sent_token(session: &Session, url: &str) -> anyhow::Result<Option<String>> {
+        let request = authorized(reqwest::Client::new().get(url), session, url)?.build()?;
+        Ok(request
+            .headers()
+            .get(AUTHORIZATION)
+            .map(|v| v.to_str().unwrap().to_string()))
+    }
+
+    #[test]
+    fn host_credential_goes_only_to_its_host() {
+        let session = session();
+        assert_eq!(
+            session.token_for("https://registry.acme.com/registry.json"),
+            Some("acme-token")
+        );
+        assert_eq!(
+            session.token_for("https://REGISTRY.acme.com/x"),
+            Some("acme-token")
+        );
+        assert_eq!(
+            session.token_for("https://evil.registry.acme.com.io/x"),
+            None
+        );
+        assert_eq!(session.token_for("https://acme.com/registry.json"), None);
+    }
+
+    #[test]
+    fn profile_token_goes_only_to_api_origin() {
+        let session = session();
+        assert_eq!(
+            session.token_for("https://api.rustskill.com/skills/content/rust/x"),
+            Some("profile-token")
+        );
+        assert_eq!(
+            session.token_for("https://api.rustskill.com:443/x"),
+            Some("profile-token")
+        );
+        for url in [
+            "https://api.rustskill.com:8443/x",
+            "http://api.rustskill.com/x",
+            "https://rustskill.com/x",
+            "https://api.rustskill.com.evil.io/x",
+            "https://raw.githubusercontent.com/x/registry.json",
+            "not a url",
+        ] {
+            assert_eq!(session.token_for(url), None, "vazou para {}", url);
+        }
+    }
+
+    #[test]
+    fn authorized_attaches_only_the_matching_token() {
+        let session = session();
+        assert_eq!(
+            sent_token(&session, "https://registry.acme.com/registry.json").unwrap(),
+            Some("Bearer acme-token".to_string())
+        );
+        assert_eq!(
+            sent_token(&session, "https://api.rustskill.com/x").unwrap(),
+            Some("Bearer profile-token".to_string())
+        );
+        assert_eq!(sent_token(&session, "https://example.com/x").unwrap(), None);
+    }
+
+    #[test]
+    fn credentials_require_https_unless_insecure() {
+        let mut session = session();
+        assert!(session.ensure_secure("https://registry.acme.com/x").is_ok());
+        assert!(session.ensure_secure("http://registry.acme.com/x").is_err());
+        assert!(sent_token(&session, "http://registry.acme.com/x").is_err());
+        // Sem token para o destino, HTTP puro não envia nada e segue permitido
+        assert_eq!(sent_token(&session, "http://example.com/x").unwrap(), None);
+
+        session.allow_insecure = true;
+        assert!(session.ensure_secure("http://registry.acme.com/x").is_ok());
+        assert_eq!(
+            sent_token(&session, "http://registry.acme.com/x").unwrap(),
+            Some("Bearer acme-token".to_string())
+        );
+    }
+}
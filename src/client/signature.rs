@@ -0,0 +1,36 @@
+use minisign_verify::{PublicKey, Signature};
+
+/// Extensão do arquivo de assinatura publicado ao lado de cada recurso (`<url>.minisig`)
+pub const SIGNATURE_SUFFIX: &str = ".minisig";
+
+/// Verifica a assinatura minisign de `content` contra as chaves confiáveis do registry.
+/// Falha se nenhuma chave validar: o conteúdo nunca é usado sem assinatura válida.
+pub fn verify(
+    content: &[u8],
+    signature: &str,
+    trusted_keys: &[String],
+    what: &str,
+) -> anyhow::Result<()> {
+    let signature = Signature::decode(signature)
+        .map_err(|e| anyhow::anyhow!("❌ Assinatura ilegível para {}: {}", what, e))?;
+
+    for key in trusted_keys {
+        let public_key = PublicKey::from_base64(key.trim())
+            .map_err(|e| anyhow::anyhow!("❌ Chave pública inválida '{}': {}", key, e))?;
+        if public_key.verify(content, &signature, false).is_ok() {
+            return Ok(());
+        }
+    }
+
+    anyhow::bail!(
+        "❌ Assinatura de {} não confere com nenhuma chave confiável. Nada foi instalado.",
+        what
+    )
+}
+
+/// Valida o formato de uma chave antes de gravá-la no perfil
+pub fn check_key(key: &str) -> anyhow::Result<()> {
+    PublicKey::from_base64(key.trim())
+        .map(|_| ())
+        .map_err(|e| anyhow::anyhow!("❌ Chave pública minisign inválida: {}", e))
+}
@@ -0,0 +1,611 @@
+use crate::client::downloader::{self, SkillEntry, SkillPayload};
+use crate::client::profile::Session;
+use crate::client::signature::{self, SIGNATURE_SUFFIX};
+use crate::core::authoring::{self, SKILL_BODY, SKILL_MANIFEST};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// De onde o `add` busca a skill
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkillSource {
+    /// Alias do registry (ex: `rust/clean-code`)
+    Registry(String),
+    /// Arquivo `.mdc` ou diretório gerado pelo `rustskill new`
+    Local(PathBuf),
+    /// `git+<repo>#<caminho>@<ref>`; caminho e ref são opcionais
+    Git {
+        repo: String,
+        path: String,
+        reference: Option<String>,
+    },
+    /// `.mdc` servido por HTTP(S)
+    Url(String),
+}
+
+impl SkillSource {
+    pub fn parse(input: &str) -> anyhow::Result<Self> {
+        if let Some(rest) = input.strip_prefix("git+") {
+            let (repo, fragment) = rest.split_once('#').unwrap_or((rest, ""));
+            let (path, reference) = match fragment.rsplit_once('@') {
+                Some((path, reference)) => (path, Some(reference)),
+                None => (fragment, None),
+            };
+            let reference = reference.filter(|r| !r.is_empty());
+            // `ext::` e afins executam comandos: só URLs e caminhos comuns são aceitos
+            if repo.is_empty()
+                || repo.starts_with('-')
+                || repo.contains("::")
+                || reference.is_some_and(|r| r.starts_with('-'))
+            {
+                anyhow::bail!(
+                    "❌ Origem git inválida: '{}'. Use git+https://host/repo#caminho@ref.",
+                    input
+                );
+            }
+            return Ok(SkillSource::Git {
+                repo: repo.to_string(),
+                path: path.trim_matches('/').to_string(),
+                reference: reference.map(str::to_string),
+            });
+        }
+        if input.starts_with("https://") || input.starts_with("http://") {
+            return Ok(SkillSource::Url(input.to_string()));
+        }
+
+        // Só formas explícitas: `rust/clean-code` continua sendo o alias mesmo que exista
+        // um diretório com esse nome (o `rustskill new` cria exatamente isso)
+        let looks_local = input.starts_with(['.', '/', '~'])
+            || Path::new(input).is_absolute()
+            || input.ends_with(".mdc");
+        if looks_local {
+            return Ok(SkillSource::Local(expand_home(input)));
+        }
+        Ok(SkillSource::Registry(input.to_string()))
+    }
+
+    /// Forma de `path` que o `parse` reconhece como local (`myskill` → `./myskill`)
+    pub fn local_arg(path: &Path) -> String {
+        let shown = path.display().to_string();
+        if path.is_absolute() || shown.starts_with(['.', '~']) {
+            shown
+        } else {
+            format!("./{}", shown)
+        }
+    }
+}
+
+/// `~/skills/x` → diretório pessoal do usuário; o shell não expande o `~` entre aspas
+fn expand_home(input: &str) -> PathBuf {
+    match input.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with(['/', '\\']) => dirs::home_dir()
+            .map(|home| home.join(rest.trim_start_matches(['/', '\\'])))
+            .unwrap_or_else(|| PathBuf::from(input)),
+        _ => PathBuf::from(input),
+    }
+}
+
+/// Skill obtida fora do registry, pronta para o mesmo pipeline de instalação
+#[derive(Debug)]
+pub struct Resolved {
+    /// Entrada sintética, usada pela política da organização
+    pub entry: SkillEntry,
+    pub payload: SkillPayload,
+}
+
+/// HTTP puro só com `--insecure`: conteúdo sem TLS pode ser trocado no caminho
+fn ensure_transport(session: &Session, url: &str) -> anyhow::Result<()> {
+    if url.starts_with("http://") && !session.allow_insecure {
+        anyhow::bail!(
+            "❌ Recusado: {} não usa HTTPS. Para testes locais use --insecure.",
+            url
+        );
+    }
+    Ok(())
+}
+
+fn file_stem(name: &str) -> String {
+    let stem = name.rsplit(['/', '\\']).next().unwrap_or(name);
+    let stem = stem.split(['?', '#']).next().unwrap_or(stem);
+    stem.strip_suffix(".mdc").unwrap_or(stem).to_string()
+}
+
+/// Monta a skill a partir de um `.mdc` avulso; o frontmatter original dá lugar ao do RustSkill
+fn from_mdc(kind: &str, name: &str, origin: &str, content: &str) -> anyhow::Result<Resolved> {
+    let stem = file_stem(name);
+    if stem.trim().is_empty() {
+        anyhow::bail!(
+            "❌ Não foi possível extrair o nome da skill de '{}'.",
+            origin
+        );
+    }
+    let (globs, body) = authoring::split_frontmatter(content);
+    Ok(Resolved {
+        entry: SkillEntry {
+            id: format!("{}/{}", kind, stem),
+            name: stem.clone(),
+            category: kind.to_string(),
+            url: origin.to_string(),
+            premium: false,
+            triggers: None,
+            sha256: None,
+            size: None,
+            registry: origin.to_string(),
+        },
+        payload: SkillPayload {
+            name: stem.clone(),
+            instruction: body.to_string(),
+            file_name: format!("{}.mdc", stem),
+            signature: None,
+            globs: (!globs.is_empty()).then_some(globs),
+        },
+    })
+}
+
+/// Arquivo `.mdc` ou diretório de skill (`skill.toml` + `skill.mdc`) em disco
+fn from_path(kind: &str, path: &Path, origin: &str) -> anyhow::Result<(Resolved, PathBuf)> {
+    if path.is_dir() {
+        let (manifest, body) = authoring::load_skill_dir(path)?;
+        let (front_globs, body) = authoring::split_frontmatter(&body);
+        let globs = if manifest.globs.is_empty() {
+            front_globs
+        } else {
+            manifest.globs.clone()
+        };
+        let file_name = manifest
+            .file_name
+            .clone()
+            .unwrap_or_else(|| manifest.id.replace('/', "-") + ".mdc");
+        let resolved = Resolved {
+            entry: SkillEntry {
+                id: manifest.id.clone(),
+                name: manifest.name.clone(),
+                category: manifest.category.clone(),
+                url: origin.to_string(),
+                premium: false,
+                triggers: Some(manifest.triggers.clone()),
+                sha256: None,
+                size: None,
+                registry: origin.to_string(),
+            },
+            payload: SkillPayload {
+                name: manifest.name,
+                instruction: body.to_string(),
+                file_name,
+                signature: None,
+                globs: (!globs.is_empty()).then_some(globs),
+            },
+        };
+        return Ok((resolved, path.join(SKILL_BODY)));
+    }
+
+    let content = fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("❌ Não foi possível ler {}: {}", path.display(), e))?;
+    let name = path.to_string_lossy();
+    Ok((from_mdc(kind, &name, origin, &content)?, path.to_path_buf()))
+}
+
+fn sidecar_path(file: &Path) -> PathBuf {
+    PathBuf::from(format!("{}{}", file.display(), SIGNATURE_SUFFIX))
+}
+
+/// Recusa um arquivo do clone que, seguindo links simbólicos, saia do repositório;
+/// arquivos ausentes passam e falham depois, na leitura
+fn ensure_inside(root: &Path, file: &Path) -> anyhow::Result<()> {
+    match file.canonicalize() {
+        Ok(real) if !real.starts_with(root) => {
+            let shown = file.strip_prefix(root).unwrap_or(file);
+            anyhow::bail!("❌ '{}' aponta para fora do repositório.", shown.display())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Com chaves confiáveis para a origem, exige o `.minisig` ao lado do arquivo
+fn verify_sidecar(keys: &[String], file: &Path, id: &str) -> anyhow::Result<()> {
+    if keys.is_empty() {
+        return Ok(());
+    }
+    let sig_path = sidecar_path(file);
+    let sig = fs::read_to_string(&sig_path).map_err(|_| {
+        anyhow::anyhow!(
+            "❌ Assinatura obrigatória ausente em {}.",
+            sig_path.display()
+        )
+    })?;
+    signature::verify(&fs::read(file)?, &sig, keys, id)
+}
+
+/// Diretório temporário do clone, apagado ao sair de escopo
+struct Checkout(PathBuf);
+
+impl Drop for Checkout {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn git(dir: Option<&Path>, args: &[&str]) -> anyhow::Result<()> {
+    let mut command = Command::new("git");
+    if let Some(dir) = dir {
+        command.current_dir(dir);
+    }
+    let output = command
+        .args(args)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .map_err(|e| anyhow::anyhow!("❌ Não foi possível executar o git: {}", e))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "❌ git {} falhou: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Clona o repositório e faz checkout da ref pedida (bloqueante)
+fn checkout(repo: &str, reference: Option<&str>) -> anyhow::Result<Checkout> {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos();
+    let dir = std::env::temp_dir().join(format!("rustskill-git-{}-{}", std::process::id(), nanos));
+    let checkout = Checkout(dir);
+
+    let target = checkout.0.to_string_lossy().to_string();
+    git(
+        None,
+        &["clone", "--quiet", "--no-checkout", "--", repo, &target],
+    )?;
+    let reference = reference.unwrap_or("HEAD");
+    let commit = format!("{}^{{commit}}", reference);
+    git(
+        Some(&checkout.0),
+        &["rev-parse", "--verify", "--quiet", &commit],
+    )
+    .map_err(|_| anyhow::anyhow!("❌ Ref '{}' não encontrada em {}.", reference, repo))?;
+    git(
+        Some(&checkout.0),
+        &["checkout", "--quiet", "--detach", &commit],
+    )?;
+    Ok(checkout)
+}
+
+/// Busca a skill de uma origem fora do registry
+pub async fn resolve(session: &Session, source: &SkillSource) -> anyhow::Result<Resolved> {
+    match source {
+        SkillSource::Registry(alias) => {
+            anyhow::bail!(
+                "❌ '{}' é um alias do registry, não uma origem externa.",
+                alias
+            )
+        }
+        SkillSource::Local(path) => {
+            let origin = path.to_string_lossy().to_string();
+            let (resolved, file) = from_path("local", path, &origin)?;
+            verify_sidecar(session.keys_for(&origin), &file, &resolved.entry.id)?;
+            Ok(resolved)
+        }
+        SkillSource::Git {
+            repo,
+            path,
+            reference,
+        } => {
+            if repo.contains("://") {
+                ensure_transport(session, repo)?;
+            }
+            let (repo, path, reference) = (repo.clone(), path.clone(), reference.clone());
+            let keys = session.keys_for(&repo).to_vec();
+            tokio::task::spawn_blocking(move || {
+                let clone = checkout(&repo, reference.as_deref())?;
+                let root = clone.0.canonicalize()?;
+                let target = root
+                    .join(&path)
+                    .canonicalize()
+                    .map_err(|_| anyhow::anyhow!("❌ '{}' não existe em {}.", path, repo))?;
+                // O caminho pedido (ou um link dentro do repositório) não pode sair do clone
+                if !target.starts_with(&root) {
+                    anyhow::bail!("❌ '{}' aponta para fora do repositório.", path);
+                }
+                // Idem para cada arquivo lido: manifesto, corpo e assinatura
+                if target.is_dir() {
+                    ensure_inside(&root, &target.join(SKILL_MANIFEST))?;
+                    ensure_inside(&root, &target.join(SKILL_BODY))?;
+                }
+
+                let (resolved, file) = from_path("git", &target, &repo)?;
+                ensure_inside(&root, &sidecar_path(&file))?;
+                verify_sidecar(&keys, &file, &resolved.entry.id)?;
+                Ok(resolved)
+            })
+            .await?
+        }
+        SkillSource::Url(url) => {
+            ensure_transport(session, url)?;
+            let client = reqwest::Client::builder()
+                .user_agent("rustskill-cli")
+                .build()?;
+            let response = downloader::authorized(client.get(url), session, url)?
+                .send()
+                .await?;
+            if !response.status().is_success() {
+                anyhow::bail!("❌ Erro ao baixar {} (Status: {}).", url, response.status());
+            }
+            let bytes = response.bytes().await?.to_vec();
+            let content = String::from_utf8(bytes.clone())
+                .map_err(|_| anyhow::anyhow!("❌ O conteúdo de {} não é UTF-8 válido.", url))?;
+            let resolved = from_mdc("url", url, url, &content)?;
+
+            let keys = session.keys_for(url);
+            if !keys.is_empty() {
+                let sig = downloader::fetch_signature(&client, session, url).await?;
+                signature::verify(&bytes, &sig, keys, &resolved.entry.id)?;
+            }
+            Ok(resolved)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args([
+                "-c",
+                "init.defaultBranch=main",
+                "-c",
+                "commit.gpgsign=false",
+            ])
+            .args(args)
+            .current_dir(dir)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {:?} falhou", args);
+    }
+
+    /// Repositório bare com `skills/demo` (skill.toml + skill.mdc) marcado como v1
+    fn bare_repo(root: &Path) -> PathBuf {
+        let bare = root.join("skills.git");
+        let work = root.join("work");
+        fs::create_dir_all(&work).unwrap();
+        run_git(root, &["init", "--quiet", "--bare", "skills.git"]);
+        run_git(&work, &["init", "--quiet"]);
+
+        let skill = work.join("skills/demo");
+        fs::create_dir_all(&skill).unwrap();
+        fs::write(
+            skill.join(SKILL_MANIFEST),
+            "id = \"rust/demo\"\nname = \"Demo\"\ncategory = \"Rust\"\nglobs = [\"**/*.rs\"]\n",
+        )
+        .unwrap();
+        fs::write(skill.join(SKILL_BODY), "# Demo\n\nUse Result.\n").unwrap();
+        // Links versionados que apontam para fora do clone
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::symlink;
+            let secret = root.join("secret.txt");
+            fs::write(&secret, "id_rsa").unwrap();
+            symlink("/etc", work.join("escape")).unwrap();
+
+            let body_leak = work.join("skills/body-leak");
+            fs::create_dir_all(&body_leak).unwrap();
+            fs::copy(skill.join(SKILL_MANIFEST), body_leak.join(SKILL_MANIFEST)).unwrap();
+            symlink(&secret, body_leak.join(SKILL_BODY)).unwrap();
+
+            let manifest_leak = work.join("skills/manifest-leak");
+            fs::create_dir_all(&manifest_leak).unwrap();
+            symlink(&secret, manifest_leak.join(SKILL_MANIFEST)).unwrap();
+            fs::write(manifest_leak.join(SKILL_BODY), "# Demo\n").unwrap();
+
+            fs::write(work.join("skills/signed.mdc"), "Use Result.\n").unwrap();
+            symlink(&secret, work.join("skills/signed.mdc.minisig")).unwrap();
+        }
+
+        run_git(&work, &["add", "."]);
+        run_git(&work, &["commit", "--quiet", "-m", "demo"]);
+        run_git(&work, &["tag", "v1"]);
+        let remote = bare.to_string_lossy().to_string();
+        run_git(
+            &work,
+            &["push", "--quiet", &remote, "HEAD:refs/heads/main", "v1"],
+        );
+        bare
+    }
+
+    fn git_source(repo: &Path, fragment: &str) -> SkillSource {
+        SkillSource::parse(&format!("git+{}#{}", repo.display(), fragment)).unwrap()
+    }
+
+    #[test]
+    fn parse_registry_aliases() {
+        assert_eq!(
+            SkillSource::parse("rust/clean-code").unwrap(),
+            SkillSource::Registry("rust/clean-code".to_string())
+        );
+        // Diretório existente com a forma de um alias continua sendo o alias
+        assert_eq!(
+            SkillSource::parse("src/client").unwrap(),
+            SkillSource::Registry("src/client".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_local_paths() {
+        for input in ["./rust/clean-code", "../x", "/tmp/skill", "rust/x.mdc"] {
+            assert_eq!(
+                SkillSource::parse(input).unwrap(),
+                SkillSource::Local(PathBuf::from(input))
+            );
+        }
+        if let Some(home) = dirs::home_dir() {
+            assert_eq!(
+                SkillSource::parse("~/skills/x").unwrap(),
+                SkillSource::Local(home.join("skills/x"))
+            );
+        }
+    }
+
+    #[test]
+    fn local_arg_parses_as_local() {
+        for dir in [
+            "myskill",
+            "rust/clean-code",
+            "./rust/x",
+            "../x",
+            "/tmp/skill",
+        ] {
+            let arg = SkillSource::local_arg(Path::new(dir));
+            assert!(
+                matches!(SkillSource::parse(&arg).unwrap(), SkillSource::Local(_)),
+                "{} virou {}",
+                dir,
+                arg
+            );
+        }
+        assert_eq!(SkillSource::local_arg(Path::new("myskill")), "./myskill");
+    }
+
+    #[test]
+    fn parse_urls() {
+        for input in ["https://example.com/x.mdc", "http://localhost:8080/x.mdc"] {
+            assert_eq!(
+                SkillSource::parse(input).unwrap(),
+                SkillSource::Url(input.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn parse_git_sources() {
+        assert_eq!(
+            SkillSource::parse("git+https://host/repo.git#skills/x/@v1.2").unwrap(),
+            SkillSource::Git {
+                repo: "https://host/repo.git".to_string(),
+                path: "skills/x".to_string(),
+                reference: Some("v1.2".to_string()),
+            }
+        );
+        assert_eq!(
+            SkillSource::parse("git+https://host/repo.git").unwrap(),
+            SkillSource::Git {
+                repo: "https://host/repo.git".to_string(),
+                path: String::new(),
+                reference: None,
+            }
+        );
+        assert_eq!(
+            SkillSource::parse("git+ssh://git@host/repo#x@").unwrap(),
+            SkillSource::Git {
+                repo: "ssh://git@host/repo".to_string(),
+                path: "x".to_string(),
+                reference: None,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_rejects_dangerous_git_sources() {
+        for input in [
+            "git+ext::sh -c touch% /tmp/pwned",
+            "git+fd::3",
+            "git+",
+            "git+--upload-pack=touch /tmp/pwned",
+            "git+https://host/repo#x@--output=/tmp/pwned",
+        ] {
+            assert!(SkillSource::parse(input).is_err(), "aceitou {}", input);
+        }
+    }
+
+    #[tokio::test]
+    async fn resolves_git_skill_dir_at_tag() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = bare_repo(tmp.path());
+        let session = Session::for_tests("http://127.0.0.1:9");
+
+        let resolved = resolve(&session, &git_source(&repo, "skills/demo@v1"))
+            .await
+            .unwrap();
+        assert_eq!(resolved.entry.id, "rust/demo");
+        assert_eq!(resolved.entry.registry, repo.display().to_string());
+        assert_eq!(resolved.payload.instruction, "# Demo\n\nUse Result.\n");
+        assert_eq!(resolved.payload.globs, Some(vec!["**/*.rs".to_string()]));
+        assert_eq!(resolved.payload.file_name, "rust-demo.mdc");
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_git_ref() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = bare_repo(tmp.path());
+        let session = Session::for_tests("http://127.0.0.1:9");
+
+        let err = resolve(&session, &git_source(&repo, "skills/demo@v9"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("não encontrada"), "{}", err);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn rejects_git_path_outside_repo() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = bare_repo(tmp.path());
+        let session = Session::for_tests("http://127.0.0.1:9");
+
+        // Subidas suficientes para chegar a `/etc` de qualquer diretório temporário
+        for fragment in [
+            "../../../../../../../../../../etc@v1",
+            "escape@v1",
+            "skills/body-leak@v1",
+            "skills/manifest-leak@v1",
+        ] {
+            let err = resolve(&session, &git_source(&repo, fragment))
+                .await
+                .unwrap_err();
+            assert!(err.to_string().contains("fora do repositório"), "{}", err);
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn rejects_git_signature_outside_repo() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = bare_repo(tmp.path());
+        let mut session = Session::for_tests("http://127.0.0.1:9");
+        session.trusted_keys.insert(
+            repo.display().to_string(),
+            vec!["RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3".to_string()],
+        );
+
+        let err = resolve(&session, &git_source(&repo, "skills/signed.mdc@v1"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("fora do repositório"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn requires_sidecar_when_keys_are_trusted() {
+        let tmp = tempfile::tempdir().unwrap();
+        let file = tmp.path().join("demo.mdc");
+        fs::write(&file, "Use Result.\n").unwrap();
+        let origin = file.to_string_lossy().to_string();
+
+        let mut session = Session::for_tests("http://127.0.0.1:9");
+        let source = SkillSource::Local(file.clone());
+        let resolved = resolve(&session, &source).await.unwrap();
+        assert_eq!(resolved.entry.id, "local/demo");
+
+        session.trusted_keys.insert(
+            origin,
+            vec!["RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3".to_string()],
+        );
+        let err = resolve(&session, &source).await.unwrap_err();
+        assert!(
+            err.to_string().contains("Assinatura obrigatória"),
+            "{}",
+            err
+        );
+    }
+}
@@ -0,0 +1,625 @@
+use crate::client::signature::{self, SIGNATURE_SUFFIX};
+use crate::utils::fs::write_atomic;
+use crate::utils::hash::sha256_hex;
+use chrono::{DateTime, Duration, Utc};
+use self_update::update::{Release, ReleaseAsset};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::mpsc;
+
+pub const REPO_OWNER: &str = "cleitonaugusto";
+pub const REPO_NAME: &str = "rustskill";
+pub const BIN_NAME: &str = "rustskill";
+
+/// Sobrescreve a origem das atualizações: `github`, uma URL HTTPS ou um diretório local
+pub const UPDATE_SOURCE_ENV: &str = "RUSTSKILL_UPDATE_SOURCE";
+/// Manifesto de releases dos espelhos (HTTPS ou diretório local)
+pub const MANIFEST_FILE: &str = "releases.json";
+/// Desliga o aviso de nova versão (ex.: em CI)
+pub const NO_UPDATE_CHECK_ENV: &str = "RUSTSKILL_NO_UPDATE_CHECK";
+/// Cache da última consulta de versões, ao lado da configuração
+const CACHE_FILE: &str = "update-check.toml";
+/// Intervalo mínimo entre consultas do aviso automático
+const CHECK_INTERVAL_HOURS: i64 = 24;
+/// Quanto o fim do comando espera pela consulta de versão ainda em andamento
+const CHECK_WAIT: std::time::Duration = std::time::Duration::from_millis(1500);
+
+/// Canal de releases: `stable` ignora pré-releases (`0.5.0-beta.1`), `beta` inclui
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+    #[default]
+    Stable,
+    Beta,
+}
+
+impl FromStr for Channel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "stable" => Ok(Channel::Stable),
+            "beta" => Ok(Channel::Beta),
+            other => Err(format!("canal '{}' inválido (use stable ou beta)", other)),
+        }
+    }
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Channel::Stable => write!(f, "stable"),
+            Channel::Beta => write!(f, "beta"),
+        }
+    }
+}
+
+/// De onde vêm as releases. Espelhos (`manifest` e `local`) seguem o layout
+/// `<base>/releases.json` + `<base>/v<versão>/<arquivo>`, com o manifesto no formato
+/// `{"releases": [{"version": "0.5.0", "assets": ["rustskill-linux-x86_64", "SHA256SUMS", ...]}]}`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum UpdateSource {
+    /// GitHub ou GitHub Enterprise (`api_url` = `https://github.empresa.com/api/v3`)
+    Github {
+        #[serde(default = "default_owner")]
+        owner: String,
+        #[serde(default = "default_repo")]
+        repo: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        api_url: Option<String>,
+    },
+    /// Diretório HTTPS com `releases.json`
+    Manifest { url: String },
+    /// Diretório local (ou compartilhamento de rede) com `releases.json`
+    Local { path: PathBuf },
+}
+
+fn default_owner() -> String {
+    REPO_OWNER.to_string()
+}
+
+fn default_repo() -> String {
+    REPO_NAME.to_string()
+}
+
+impl Default for UpdateSource {
+    fn default() -> Self {
+        UpdateSource::Github {
+            owner: default_owner(),
+            repo: default_repo(),
+            api_url: None,
+        }
+    }
+}
+
+impl FromStr for UpdateSource {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("github") {
+            return Ok(UpdateSource::default());
+        }
+        if s.contains("://") {
+            return Ok(UpdateSource::Manifest { url: s.to_string() });
+        }
+        Ok(UpdateSource::Local { path: s.into() })
+    }
+}
+
+impl fmt::Display for UpdateSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UpdateSource::Github {
+                owner,
+                repo,
+                api_url,
+            } => match api_url {
+                Some(api) => write!(f, "{}/{} em {}", owner, repo, api),
+                None => write!(f, "github.com/{}/{}", owner, repo),
+            },
+            UpdateSource::Manifest { url } => write!(f, "{}", url),
+            UpdateSource::Local { path } => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Manifest {
+    releases: Vec<ManifestRelease>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ManifestRelease {
+    version: String,
+    #[serde(default)]
+    assets: Vec<String>,
+}
+
+impl ManifestRelease {
+    fn into_release(self, base: &str) -> Release {
+        let version = normalize_version(&self.version);
+        let assets = self
+            .assets
+            .into_iter()
+            .map(|name| ReleaseAsset {
+                download_url: format!("{}/v{}/{}", base, version, name),
+                name,
+            })
+            .collect();
+        Release {
+            name: format!("v{}", version),
+            version,
+            date: String::new(),
+            body: None,
+            assets,
+        }
+    }
+}
+
+/// Seção `[update]` da configuração do usuário
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateConfig {
+    /// Aviso diário de nova versão ao final dos comandos
+    #[serde(default = "default_notify")]
+    pub notify: bool,
+    #[serde(default)]
+    pub channel: Channel,
+    /// Chaves minisign extras aceitas para o SHA256SUMS das releases
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trusted_keys: Vec<String>,
+    #[serde(default)]
+    pub source: UpdateSource,
+}
+
+fn default_notify() -> bool {
+    true
+}
+
+impl Default for UpdateConfig {
+    fn default() -> Self {
+        Self {
+            notify: true,
+            channel: Channel::Stable,
+            trusted_keys: Vec::new(),
+            source: UpdateSource::default(),
+        }
+    }
+}
+
+impl UpdateConfig {
+    pub fn notify_enabled(&self) -> bool {
+        self.notify && std::env::var_os(NO_UPDATE_CHECK_ENV).is_none()
+    }
+
+    /// Origem em vigor: `RUSTSKILL_UPDATE_SOURCE` vence a configuração
+    pub fn source(&self) -> anyhow::Result<UpdateSource> {
+        match std::env::var(UPDATE_SOURCE_ENV) {
+            Ok(value) if !value.trim().is_empty() => value.parse(),
+            _ => Ok(self.source.clone()),
+        }
+    }
+}
+
+/// `v0.3.1` e `0.3.1` são a mesma versão
+pub fn normalize_version(version: &str) -> String {
+    version.trim().trim_start_matches('v').to_string()
+}
+
+fn is_prerelease(version: &str) -> bool {
+    version.contains('-')
+}
+
+/// `true` se `other` é mais nova que `current` (semver, com ou sem o `v` da tag)
+pub fn is_newer(current: &str, other: &str) -> bool {
+    self_update::version::bump_is_greater(&normalize_version(current), &normalize_version(other))
+        .unwrap_or(false)
+}
+
+/// Lê um arquivo de um espelho: HTTPS ou caminho local (bloqueante)
+fn read_location(location: &str) -> anyhow::Result<Vec<u8>> {
+    if location.contains("://") {
+        if !location.starts_with("https://") {
+            anyhow::bail!(
+                "❌ Recusado: espelhos de atualização precisam de HTTPS ({}).",
+                location
+            );
+        }
+        let mut bytes = Vec::new();
+        self_update::Download::from_url(location).download_to(&mut bytes)?;
+        Ok(bytes)
+    } else {
+        fs::read(location).map_err(|e| anyhow::anyhow!("❌ Falha ao ler {}: {}", location, e))
+    }
+}
+
+fn manifest_releases(base: &str) -> anyhow::Result<Vec<Release>> {
+    let base = base.trim_end_matches('/');
+    let bytes = read_location(&format!("{}/{}", base, MANIFEST_FILE))?;
+    let manifest: Manifest = serde_json::from_slice(&bytes)
+        .map_err(|e| anyhow::anyhow!("❌ {} inválido em {}: {}", MANIFEST_FILE, base, e))?;
+    Ok(manifest
+        .releases
+        .into_iter()
+        .map(|r| r.into_release(base))
+        .collect())
+}
+
+fn releases(source: &UpdateSource) -> anyhow::Result<Vec<Release>> {
+    match source {
+        UpdateSource::Github {
+            owner,
+            repo,
+            api_url,
+        } => {
+            let mut list = self_update::backends::github::ReleaseList::configure();
+            list.repo_owner(owner).repo_name(repo);
+            if let Some(api) = api_url {
+                list.with_url(api.trim_end_matches('/'));
+            }
+            Ok(list.build()?.fetch()?)
+        }
+        UpdateSource::Manifest { url } => manifest_releases(url),
+        UpdateSource::Local { path } => manifest_releases(&path.to_string_lossy()),
+    }
+}
+
+/// Release mais recente do canal (bloqueante: use fora do runtime async)
+pub fn latest_release(source: &UpdateSource, channel: Channel) -> anyhow::Result<Option<Release>> {
+    Ok(releases(source)?
+        .into_iter()
+        .filter(|r| channel == Channel::Beta || !is_prerelease(&r.version))
+        .fold(None, |best: Option<Release>, r| match best {
+            Some(b) if !is_newer(&b.version, &r.version) => Some(b),
+            _ => Some(r),
+        }))
+}
+
+/// Release de uma versão específica (bloqueante)
+pub fn find_release(source: &UpdateSource, version: &str) -> anyhow::Result<Release> {
+    let version = normalize_version(version);
+    releases(source)?
+        .into_iter()
+        .find(|r| normalize_version(&r.version) == version)
+        .ok_or_else(|| anyhow::anyhow!("❌ Versão {} não encontrada nas releases.", version))
+}
+
+/// Lista de checksums publicada em cada release (formato do `sha256sum`)
+pub const CHECKSUMS_ASSET: &str = "SHA256SUMS";
+/// Chave minisign das releases oficiais, embutida pelo pipeline de build
+const RELEASE_PUBLIC_KEY: Option<&str> = option_env!("RUSTSKILL_RELEASE_PUBKEY");
+/// Sufixo da cópia do binário anterior, usada pelo `upgrade --rollback`
+const PREVIOUS_SUFFIX: &str = "previous";
+
+/// Nome do binário publicado para esta plataforma (mesmo padrão do install.sh)
+pub fn asset_name() -> String {
+    let os = std::env::consts::OS;
+    let arch = match (os, std::env::consts::ARCH) {
+        ("macos", "aarch64") => "arm64",
+        (_, arch) => arch,
+    };
+    let ext = if cfg!(windows) { ".exe" } else { "" };
+    format!("{}-{}-{}{}", BIN_NAME, os, arch, ext)
+}
+
+/// Chaves aceitas para assinar o SHA256SUMS: a embutida no build e as da configuração
+fn release_keys(config: &UpdateConfig) -> Vec<String> {
+    RELEASE_PUBLIC_KEY
+        .map(str::to_string)
+        .into_iter()
+        .chain(config.trusted_keys.iter().cloned())
+        .collect()
+}
+
+async fn download(client: &reqwest::Client, url: &str) -> anyhow::Result<Vec<u8>> {
+    // Espelho local: o caminho do arquivo já é a "URL" do asset
+    if !url.contains("://") {
+        return read_location(url);
+    }
+    let response = client
+        .get(url)
+        .header(reqwest::header::ACCEPT, "application/octet-stream")
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        anyhow::bail!(
+            "❌ Falha ao baixar {} (Status: {}).",
+            url,
+            response.status()
+        );
+    }
+    Ok(response.bytes().await?.to_vec())
+}
+
+/// Hash esperado de `name` em um arquivo no formato `<sha256>  <arquivo>`
+fn expected_checksum(sums: &str, name: &str) -> Option<String> {
+    sums.lines().find_map(|line| {
+        let (hash, file) = line.trim().split_once(char::is_whitespace)?;
+        (file.trim().trim_start_matches('*') == name).then(|| hash.to_lowercase())
+    })
+}
+
+/// Cópia do binário anterior, ao lado do executável atual
+pub fn previous_binary() -> anyhow::Result<PathBuf> {
+    Ok(std::env::current_exe()?.with_extension(PREVIOUS_SUFFIX))
+}
+
+/// Baixa a release, confere assinatura e checksum e só então troca o binário.
+/// O binário atual fica guardado para o `upgrade --rollback`.
+pub async fn install_verified(release: &Release, config: &UpdateConfig) -> anyhow::Result<()> {
+    let keys = release_keys(config);
+    if keys.is_empty() {
+        anyhow::bail!(
+            "❌ Nenhuma chave de assinatura de releases disponível (build sem RUSTSKILL_RELEASE_PUBKEY e [update].trusted_keys vazio). Atualização recusada."
+        );
+    }
+
+    let name = asset_name();
+    let asset_url = |asset: &str| {
+        release
+            .assets
+            .iter()
+            .find(|a| a.name == asset)
+            .map(|a| a.download_url.clone())
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "❌ A release {} não publica o arquivo {}.",
+                    release.version,
+                    asset
+                )
+            })
+    };
+    let binary_url = asset_url(&name)?;
+    let sums_url = asset_url(CHECKSUMS_ASSET)?;
+    let sig_url = asset_url(&format!("{}{}", CHECKSUMS_ASSET, SIGNATURE_SUFFIX))?;
+
+    let client = reqwest::Client::builder().user_agent(BIN_NAME).build()?;
+    let sums = download(&client, &sums_url).await?;
+    let sig = String::from_utf8(download(&client, &sig_url).await?)?;
+    signature::verify(&sums, &sig, &keys, CHECKSUMS_ASSET)?;
+
+    let sums = String::from_utf8(sums)?;
+    let expected = expected_checksum(&sums, &name)
+        .ok_or_else(|| anyhow::anyhow!("❌ {} não lista o arquivo {}.", CHECKSUMS_ASSET, name))?;
+
+    let binary = download(&client, &binary_url).await?;
+    let actual = sha256_hex(&binary);
+    if actual != expected {
+        anyhow::bail!(
+            "❌ Checksum de {} não confere (esperado {}, recebido {}). Binário mantido.",
+            name,
+            expected,
+            actual
+        );
+    }
+
+    let current = std::env::current_exe()?;
+    let staged = current.with_extension("new");
+    write_atomic(&staged, &binary)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&staged, fs::Permissions::from_mode(0o755))?;
+    }
+
+    fs::copy(&current, previous_binary()?)?;
+    let replaced = self_update::self_replace::self_replace(&staged);
+    let _ = fs::remove_file(&staged);
+    replaced?;
+    Ok(())
+}
+
+/// Volta para o binário guardado na última atualização; o atual passa a ser o "anterior"
+pub fn rollback() -> anyhow::Result<()> {
+    let previous = previous_binary()?;
+    if !previous.exists() {
+        anyhow::bail!("❌ Nenhuma versão anterior guardada para restaurar.");
+    }
+
+    let current = std::env::current_exe()?;
+    let swap = current.with_extension("swap");
+    fs::copy(&current, &swap)?;
+    self_update::self_replace::self_replace(&previous)?;
+    fs::rename(&swap, &previous)?;
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct UpdateCache {
+    checked_at: DateTime<Utc>,
+    channel: Channel,
+    #[serde(default)]
+    latest: Option<String>,
+}
+
+fn cache_path() -> anyhow::Result<PathBuf> {
+    let config = confy::get_configuration_file_path("rustskill", None)?;
+    let dir = config
+        .parent()
+        .ok_or_else(|| anyhow::anyhow!("Diretório de configuração inválido."))?;
+    Ok(dir.join(CACHE_FILE))
+}
+
+fn read_cache() -> Option<UpdateCache> {
+    let content = fs::read_to_string(cache_path().ok()?).ok()?;
+    toml::from_str(&content).ok()
+}
+
+fn write_cache(cache: &UpdateCache) -> anyhow::Result<()> {
+    let path = cache_path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    write_atomic(&path, toml::to_string(cache)?.as_bytes())
+}
+
+/// Versão mais nova conhecida pelo cache, se houver; nunca acessa a rede
+pub fn cached_update(config: &UpdateConfig) -> Option<String> {
+    let cache = read_cache()?;
+    let latest = cache.latest.filter(|_| cache.channel == config.channel)?;
+    is_newer(env!("CARGO_PKG_VERSION"), &latest).then_some(latest)
+}
+
+/// Consulta de versão em segundo plano; ao sair de escopo espera por ela até `CHECK_WAIT`,
+/// para que comandos rápidos não encerrem o processo no meio da requisição
+pub struct UpdateCheck(Option<mpsc::Receiver<()>>);
+
+impl Drop for UpdateCheck {
+    fn drop(&mut self) {
+        if let Some(done) = self.0.take() {
+            let _ = done.recv_timeout(CHECK_WAIT);
+        }
+    }
+}
+
+/// Atualiza o cache em uma thread separada se a última consulta tiver mais de um dia.
+/// A tentativa é registrada antes da requisição: offline ou com limite de taxa da API,
+/// a próxima consulta também só acontece no dia seguinte. Falhas de rede são silenciosas.
+pub fn refresh_cache_in_background(config: &UpdateConfig) -> UpdateCheck {
+    let cached = read_cache().filter(|c| c.channel == config.channel);
+    if cached
+        .as_ref()
+        .is_some_and(|c| Utc::now() - c.checked_at < Duration::hours(CHECK_INTERVAL_HOURS))
+    {
+        return UpdateCheck(None);
+    }
+
+    let Ok(source) = config.source() else {
+        return UpdateCheck(None);
+    };
+    let channel = config.channel;
+    let attempt = UpdateCache {
+        checked_at: Utc::now(),
+        channel,
+        latest: cached.and_then(|c| c.latest),
+    };
+    if write_cache(&attempt).is_err() {
+        return UpdateCheck(None);
+    }
+
+    let (done, finished) = mpsc::channel();
+    std::thread::spawn(move || {
+        if let Ok(release) = latest_release(&source, channel) {
+            let _ = write_cache(&UpdateCache {
+                checked_at: Utc::now(),
+                channel,
+                latest: release.map(|r| r.version),
+            });
+        }
+        let _ = done.send(());
+    });
+    UpdateCheck(Some(finished))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUMS: &str = "\
+0a1b2c3d4e5f60718293a4b5c6d7e8f90a1b2c3d4e5f60718293a4b5c6d7e8f9  rustskill-linux-x86_64
+AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA *rustskill-windows-x86_64.exe
+1111111111111111111111111111111111111111111111111111111111111111  rustskill-linux-x86_64-musl
+";
+
+    #[test]
+    fn expected_checksum_reads_text_lines() {
+        assert_eq!(
+            expected_checksum(SUMS, "rustskill-linux-x86_64").as_deref(),
+            Some("0a1b2c3d4e5f60718293a4b5c6d7e8f90a1b2c3d4e5f60718293a4b5c6d7e8f9")
+        );
+    }
+
+    #[test]
+    fn expected_checksum_reads_binary_lines() {
+        assert_eq!(
+            expected_checksum(SUMS, "rustskill-windows-x86_64.exe").as_deref(),
+            Some(&"a".repeat(64)[..])
+        );
+    }
+
+    #[test]
+    fn expected_checksum_matches_whole_names_only() {
+        assert_eq!(expected_checksum(SUMS, "rustskill-macos-arm64"), None);
+        assert_eq!(expected_checksum(SUMS, "rustskill-linux"), None);
+        assert_eq!(
+            expected_checksum(SUMS, "rustskill-linux-x86_64-musl").as_deref(),
+            Some(&"1".repeat(64)[..])
+        );
+        assert_eq!(expected_checksum("", "rustskill-linux-x86_64"), None);
+    }
+
+    /// Espelho local com um `releases.json` contendo as versões pedidas
+    fn local_mirror(versions: &[&str]) -> (tempfile::TempDir, UpdateSource) {
+        let dir = tempfile::tempdir().unwrap();
+        let releases: Vec<String> = versions
+            .iter()
+            .map(|v| format!(r#"{{"version":"{}","assets":["{}"]}}"#, v, CHECKSUMS_ASSET))
+            .collect();
+        fs::write(
+            dir.path().join(MANIFEST_FILE),
+            format!(r#"{{"releases":[{}]}}"#, releases.join(",")),
+        )
+        .unwrap();
+        let source = UpdateSource::Local {
+            path: dir.path().to_path_buf(),
+        };
+        (dir, source)
+    }
+
+    fn latest(source: &UpdateSource, channel: Channel) -> Option<String> {
+        latest_release(source, channel).unwrap().map(|r| r.version)
+    }
+
+    #[test]
+    fn stable_channel_skips_prereleases() {
+        let (_dir, source) = local_mirror(&["0.4.0", "0.5.0-beta.1", "0.4.1"]);
+        assert_eq!(latest(&source, Channel::Stable).as_deref(), Some("0.4.1"));
+    }
+
+    #[test]
+    fn beta_channel_includes_prereleases() {
+        let (_dir, source) = local_mirror(&["0.4.0", "0.5.0-beta.1", "0.4.1"]);
+        assert_eq!(
+            latest(&source, Channel::Beta).as_deref(),
+            Some("0.5.0-beta.1")
+        );
+
+        let (_dir, source) = local_mirror(&["0.5.0-beta.1", "0.5.0"]);
+        assert_eq!(latest(&source, Channel::Beta).as_deref(), Some("0.5.0"));
+    }
+
+    #[test]
+    fn versions_may_carry_a_v_prefix() {
+        let (dir, source) = local_mirror(&["v0.3.0", "v0.10.0", "0.9.1"]);
+        let release = latest_release(&source, Channel::Stable).unwrap().unwrap();
+        assert_eq!(release.version, "0.10.0");
+        assert_eq!(
+            release.assets[0].download_url,
+            format!("{}/v0.10.0/{}", dir.path().display(), CHECKSUMS_ASSET)
+        );
+        assert!(is_newer("0.4.0", "v0.10.0"));
+    }
+
+    #[test]
+    fn find_release_returns_older_versions() {
+        let (_dir, source) = local_mirror(&["0.3.1", "0.4.0", "0.5.0"]);
+        assert_eq!(find_release(&source, "0.3.1").unwrap().version, "0.3.1");
+        assert_eq!(find_release(&source, "v0.4.0").unwrap().version, "0.4.0");
+        assert!(find_release(&source, "0.2.0").is_err());
+    }
+
+    #[test]
+    fn empty_or_missing_manifest() {
+        let (_dir, source) = local_mirror(&[]);
+        assert_eq!(latest(&source, Channel::Stable), None);
+
+        let missing = UpdateSource::Local {
+            path: PathBuf::from("/nonexistent/rustskill-mirror"),
+        };
+        assert!(latest_release(&missing, Channel::Stable).is_err());
+    }
+}
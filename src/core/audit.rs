@@ -0,0 +1,384 @@
+use crate::client::downloader::SkillEntry;
+use crate::core::lockfile::{LockedSkill, Lockfile};
+use crate::core::scanner::{Evidence, ProjectScan};
+use crate::utils::hash::sha256_hex;
+use std::fs;
+use std::path::Path;
+
+/// Peso de uma dependência direta declarada em manifesto
+const WEIGHT_MANIFEST_DIRECT: u32 = 50;
+/// Peso de uma dependência de desenvolvimento (dev-dependencies / devDependencies)
+const WEIGHT_MANIFEST_DEV: u32 = 25;
+/// Peso por arquivo que importa a dependência
+const WEIGHT_IMPORT_PER_FILE: u32 = 10;
+const MAX_IMPORT_POINTS: u32 = 40;
+/// Peso por arquivo com a extensão do gatilho / do ecossistema
+const WEIGHT_FILE: u32 = 10;
+const MAX_FILE_POINTS: u32 = 30;
+
+/// Uma evidência ponderada que sustenta a recomendação
+#[derive(Debug, Clone)]
+pub struct Signal {
+    pub description: String,
+    pub points: u32,
+}
+
+/// Skill recomendada com o placar de evidências que levou a ela
+#[derive(Debug, Clone)]
+pub struct Recommendation<'a> {
+    pub skill: &'a SkillEntry,
+    pub signals: Vec<Signal>,
+}
+
+impl Recommendation<'_> {
+    /// Confiança de 0 a 100 (soma dos pesos, saturada)
+    pub fn confidence(&self) -> u8 {
+        self.signals.iter().map(|s| s.points).sum::<u32>().min(100) as u8
+    }
+
+    pub fn reasons(&self) -> String {
+        self.signals
+            .iter()
+            .map(|s| format!("{} (+{})", s.description, s.points))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Avalia todas as skills do registry contra o scan, ordenadas pela confiança
+pub fn recommend<'a>(registry: &'a [SkillEntry], scan: &ProjectScan) -> Vec<Recommendation<'a>> {
+    let mut recs: Vec<_> = registry.iter().filter_map(|s| evaluate(s, scan)).collect();
+    recs.sort_by(|a, b| {
+        b.confidence()
+            .cmp(&a.confidence())
+            .then_with(|| a.skill.id.cmp(&b.skill.id))
+    });
+    recs
+}
+
+/// Calcula as evidências de uma skill; `None` se nada a sustenta
+pub fn evaluate<'a>(skill: &'a SkillEntry, scan: &ProjectScan) -> Option<Recommendation<'a>> {
+    let mut signals = Vec::new();
+
+    // 1. Checagem por Triggers Específicos
+    for trigger in skill.triggers.iter().flatten() {
+        signals.extend(trigger_signals(trigger, scan));
+    }
+
+    // 2. Checagem por Ecossistema (apenas quando nenhum gatilho bateu)
+    if signals.is_empty() {
+        if let Some((ext, eco)) = ecosystem_of(&skill.id) {
+            let files = scan.extension_files(ext).len() as u32;
+            if files > 0 {
+                signals.push(Signal {
+                    description: format!(
+                        "Ecossistema {} detectado ({} arquivos .{})",
+                        eco, files, ext
+                    ),
+                    points: (files * WEIGHT_FILE).min(MAX_FILE_POINTS),
+                });
+            }
+        }
+    }
+
+    if signals.is_empty() {
+        None
+    } else {
+        Some(Recommendation { skill, signals })
+    }
+}
+
+/// Sinais gerados por um único gatilho (manifesto, imports e extensão)
+pub fn trigger_signals(trigger: &str, scan: &ProjectScan) -> Vec<Signal> {
+    let t_lower = trigger.to_lowercase();
+    let mut signals = Vec::new();
+
+    let manifest = scan
+        .dependency_evidence(&t_lower)
+        .iter()
+        .filter_map(|e| match e {
+            Evidence::Manifest {
+                manifest,
+                section,
+                dev,
+            } => Some((manifest, section, *dev)),
+            _ => None,
+        })
+        .max_by_key(|(_, _, dev)| !dev);
+
+    if let Some((manifest, section, dev)) = manifest {
+        signals.push(Signal {
+            description: format!(
+                "Gatilho '{}' em {} [{}]",
+                trigger,
+                manifest.display(),
+                section
+            ),
+            points: if dev {
+                WEIGHT_MANIFEST_DEV
+            } else {
+                WEIGHT_MANIFEST_DIRECT
+            },
+        });
+    }
+
+    let imports = scan.import_files(&t_lower) as u32;
+    if imports > 0 {
+        signals.push(Signal {
+            description: format!("Gatilho '{}' importado em {} arquivos", trigger, imports),
+            points: (imports * WEIGHT_IMPORT_PER_FILE).min(MAX_IMPORT_POINTS),
+        });
+    }
+
+    let files = scan.extension_files(&t_lower).len() as u32;
+    if files > 0 {
+        signals.push(Signal {
+            description: format!("Gatilho '{}' em {} arquivos", trigger, files),
+            points: (files * WEIGHT_FILE).min(MAX_FILE_POINTS),
+        });
+    }
+
+    signals
+}
+
+/// Ecossistema inferido pelo ID da skill: (extensão, nome)
+pub fn ecosystem_of(id: &str) -> Option<(&'static str, &'static str)> {
+    let id_lower = id.to_lowercase();
+    if id_lower.contains("rust") {
+        Some(("rs", "Rust"))
+    } else if id_lower.contains("python") {
+        Some(("py", "Python"))
+    } else if id_lower.contains("go") {
+        Some(("go", "Go"))
+    } else {
+        None
+    }
+}
+
+/// Situação de uma skill em `.cursor/rules` comparada ao lockfile
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleState {
+    /// Arquivo presente e idêntico ao hash do lockfile
+    Installed,
+    /// Arquivo presente, mas sem entrada no lockfile (instalação antiga ou manual)
+    Untracked,
+    /// Arquivo editado ou truncado desde a instalação
+    Modified,
+    /// Arquivo íntegro, mas o conteúdo publicado no registry mudou
+    Stale,
+    Missing,
+}
+
+impl RuleState {
+    /// Estados que o `--fix` precisa (re)instalar e que reprovam o `--check`
+    pub fn needs_install(self) -> bool {
+        matches!(self, Self::Modified | Self::Stale | Self::Missing)
+    }
+}
+
+/// Verifica o arquivo da skill contra o hash registrado no lockfile
+pub fn rule_state(rules_dir: &Path, lock: &Lockfile, id: &str) -> RuleState {
+    match lock.get(id) {
+        Some(locked) => match fs::read(rules_dir.join(&locked.file)) {
+            Ok(bytes) if sha256_hex(&bytes) == locked.sha256 => RuleState::Installed,
+            Ok(_) => RuleState::Modified,
+            Err(_) => RuleState::Missing,
+        },
+        None => {
+            let legacy = rules_dir.join(format!("{}.mdc", id.replace('/', "-")));
+            if legacy.is_file() {
+                RuleState::Untracked
+            } else {
+                RuleState::Missing
+            }
+        }
+    }
+}
+
+/// Skills gerenciadas pelo lockfile que não existem mais no registry.
+/// Instaladas de caminho local, git ou URL nunca estiveram nele e ficam de fora.
+pub fn orphaned<'a>(lock: &'a Lockfile, registry: &[SkillEntry]) -> Vec<&'a LockedSkill> {
+    lock.skills
+        .iter()
+        .filter(|l| l.source.is_none() && !registry.iter().any(|s| s.id == l.id))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn locked(id: &str, source: Option<&str>) -> LockedSkill {
+        LockedSkill {
+            id: id.to_string(),
+            file: format!("{}.mdc", id.replace('/', "-")),
+            sha256: String::new(),
+            source: source.map(str::to_string),
+        }
+    }
+
+    fn entry(id: &str) -> SkillEntry {
+        SkillEntry {
+            id: id.to_string(),
+            name: id.to_string(),
+            category: "rust".to_string(),
+            url: String::new(),
+            premium: false,
+            triggers: None,
+            sha256: None,
+            size: None,
+            registry: String::new(),
+        }
+    }
+
+    fn triggered(id: &str, triggers: &[&str]) -> SkillEntry {
+        SkillEntry {
+            triggers: Some(triggers.iter().map(|t| t.to_string()).collect()),
+            ..entry(id)
+        }
+    }
+
+    fn manifest(dev: bool) -> Evidence {
+        Evidence::Manifest {
+            manifest: PathBuf::from("Cargo.toml"),
+            section: if dev {
+                "dev-dependencies"
+            } else {
+                "dependencies"
+            }
+            .to_string(),
+            dev,
+        }
+    }
+
+    fn imports(files: usize) -> Vec<Evidence> {
+        (0..files)
+            .flat_map(|i| {
+                // Dois imports no mesmo arquivo contam uma vez só
+                let path = PathBuf::from(format!("src/m{}.rs", i));
+                [
+                    Evidence::Import {
+                        path: path.clone(),
+                        line: 1,
+                    },
+                    Evidence::Import { path, line: 2 },
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn direct_dependency_outranks_dev_dependency() {
+        let mut scan = ProjectScan::default();
+        scan.dependencies
+            .insert("serde".into(), vec![manifest(true), manifest(false)]);
+        scan.dependencies
+            .insert("mockall".into(), vec![manifest(true)]);
+
+        let signals = trigger_signals("Serde", &scan);
+        assert_eq!(signals.len(), 1);
+        assert_eq!(signals[0].points, WEIGHT_MANIFEST_DIRECT);
+        assert!(signals[0].description.contains("[dependencies]"));
+        assert_eq!(
+            trigger_signals("mockall", &scan)[0].points,
+            WEIGHT_MANIFEST_DEV
+        );
+        assert!(trigger_signals("tokio", &scan).is_empty());
+    }
+
+    #[test]
+    fn import_and_file_points_are_capped() {
+        let mut scan = ProjectScan::default();
+        scan.dependencies.insert("tokio".into(), imports(3));
+        scan.dependencies.insert("serde".into(), imports(9));
+        scan.extensions.insert(
+            "rs".into(),
+            (0..5).map(|i| PathBuf::from(format!("{}.rs", i))).collect(),
+        );
+
+        assert_eq!(trigger_signals("tokio", &scan)[0].points, 30);
+        assert_eq!(trigger_signals("serde", &scan)[0].points, MAX_IMPORT_POINTS);
+        assert_eq!(trigger_signals("rs", &scan)[0].points, MAX_FILE_POINTS);
+    }
+
+    #[test]
+    fn recommendations_sorted_by_confidence_then_id() {
+        let mut scan = ProjectScan::default();
+        scan.dependencies
+            .insert("serde".into(), [vec![manifest(false)], imports(9)].concat());
+        scan.dependencies
+            .insert("tokio".into(), vec![manifest(false)]);
+        scan.dependencies
+            .insert("axum".into(), vec![manifest(false)]);
+        scan.extensions
+            .insert("py".into(), vec![PathBuf::from("a.py")]);
+
+        let registry = [
+            triggered("rust/tokio", &["tokio"]),
+            triggered("rust/axum", &["axum"]),
+            triggered("rust/serde", &["serde"]),
+            triggered("rust/unused", &["diesel"]),
+            entry("python/style"),
+            entry("misc/nothing"),
+        ];
+        let ranked: Vec<(&str, u8)> = recommend(&registry, &scan)
+            .iter()
+            .map(|r| (r.skill.id.as_str(), r.confidence()))
+            .collect();
+        assert_eq!(
+            ranked,
+            [
+                ("rust/serde", 90),
+                ("rust/axum", 50),
+                ("rust/tokio", 50),
+                ("python/style", 10),
+            ]
+        );
+    }
+
+    #[test]
+    fn rule_state_compares_file_with_lockfile() {
+        let dir = tempfile::tempdir().unwrap();
+        let content = b"Use Result.\n";
+        let mut lock = Lockfile::default();
+        for id in ["rust/ok", "rust/edited", "rust/truncated", "rust/gone"] {
+            lock.upsert(LockedSkill {
+                sha256: sha256_hex(content),
+                ..locked(id, None)
+            });
+        }
+        fs::write(dir.path().join("rust-ok.mdc"), content).unwrap();
+        fs::write(dir.path().join("rust-edited.mdc"), b"Use panic!.\n").unwrap();
+        fs::write(dir.path().join("rust-truncated.mdc"), b"").unwrap();
+        fs::write(dir.path().join("rust-manual.mdc"), content).unwrap();
+
+        let state = |id| rule_state(dir.path(), &lock, id);
+        assert_eq!(state("rust/ok"), RuleState::Installed);
+        assert_eq!(state("rust/edited"), RuleState::Modified);
+        assert_eq!(state("rust/truncated"), RuleState::Modified);
+        assert_eq!(state("rust/gone"), RuleState::Missing);
+        assert_eq!(state("rust/manual"), RuleState::Untracked);
+        assert_eq!(state("rust/never"), RuleState::Missing);
+        assert!(!RuleState::Untracked.needs_install());
+        assert!(RuleState::Modified.needs_install());
+    }
+
+    #[test]
+    fn orphaned_ignores_skills_from_other_sources() {
+        let lock = Lockfile {
+            skills: vec![
+                locked("rust/clean-code", None),
+                locked("rust/removed", None),
+                locked("local/draft", Some("./draft.mdc")),
+                locked("git/demo", Some("git+https://host/repo#demo@v1")),
+                locked("url/x", Some("https://example.com/x.mdc")),
+            ],
+        };
+        let orphans: Vec<&str> = orphaned(&lock, &[entry("rust/clean-code")])
+            .into_iter()
+            .map(|l| l.id.as_str())
+            .collect();
+        assert_eq!(orphans, ["rust/removed"]);
+    }
+}
@@ -0,0 +1,298 @@
+use crate::utils::fs::write_atomic;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Metadados da skill (id, categoria, gatilhos, globs, premium)
+pub const SKILL_MANIFEST: &str = "skill.toml";
+/// Instrução entregue ao agente, sem frontmatter (o instalador gera o cabeçalho)
+pub const SKILL_BODY: &str = "skill.mdc";
+/// Projeto mínimo onde a skill deve ser recomendada pelo `audit`
+pub const FIXTURE_DIR: &str = "fixtures";
+
+/// Conteúdo do `skill.toml`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SkillManifest {
+    pub id: String,
+    pub name: String,
+    pub category: String,
+    #[serde(default)]
+    pub description: String,
+    pub file_name: Option<String>,
+    #[serde(default)]
+    pub premium: bool,
+    #[serde(default)]
+    pub triggers: Vec<String>,
+    #[serde(default)]
+    pub globs: Vec<String>,
+}
+
+/// Lê `skill.toml` e `skill.mdc` de um diretório de skill
+pub fn load_skill_dir(dir: &Path) -> anyhow::Result<(SkillManifest, String)> {
+    let manifest_path = dir.join(SKILL_MANIFEST);
+    let content = fs::read_to_string(&manifest_path).map_err(|e| {
+        anyhow::anyhow!("❌ Não foi possível ler {}: {}", manifest_path.display(), e)
+    })?;
+    let manifest: SkillManifest = toml::from_str(&content)
+        .map_err(|e| anyhow::anyhow!("❌ {} inválido: {}", manifest_path.display(), e))?;
+    parse_id(&manifest.id)?;
+
+    let body_path = dir.join(SKILL_BODY);
+    let body = fs::read_to_string(&body_path)
+        .map_err(|e| anyhow::anyhow!("❌ Não foi possível ler {}: {}", body_path.display(), e))?;
+    Ok((manifest, body))
+}
+
+/// Separa o frontmatter (`---` ... `---`) de um `.mdc` do Cursor, devolvendo os globs
+/// declarados nele e o corpo; sem frontmatter, o conteúdo volta inteiro
+pub fn split_frontmatter(content: &str) -> (Vec<String>, &str) {
+    let Some(rest) = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    else {
+        return (Vec::new(), content);
+    };
+    let Some(end) = rest.find("\n---") else {
+        return (Vec::new(), content);
+    };
+
+    let globs = rest[..end]
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("globs:"))
+        .map(|value| {
+            let value = value.trim();
+            serde_json::from_str::<Vec<String>>(value).unwrap_or_else(|_| {
+                value
+                    .split(',')
+                    .map(|g| g.trim().trim_matches('"').to_string())
+                    .filter(|g| !g.is_empty())
+                    .collect()
+            })
+        })
+        .unwrap_or_default();
+
+    let body = &rest[end + "\n---".len()..];
+    let body = body.split_once('\n').map_or("", |(_, body)| body);
+    (globs, body.trim_start_matches(['\r', '\n']))
+}
+
+/// Manifesto de exemplo, gatilho e globs típicos de cada ecossistema
+struct Ecosystem {
+    manifest: &'static str,
+    content: &'static str,
+    trigger: Option<&'static str>,
+    globs: &'static [&'static str],
+}
+
+fn ecosystem(category: &str) -> Ecosystem {
+    match category {
+        "rust" => Ecosystem {
+            manifest: "Cargo.toml",
+            content: "[package]\nname = \"fixture\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\nserde = \"1\"\n",
+            trigger: Some("serde"),
+            globs: &["**/*.rs"],
+        },
+        "python" => Ecosystem {
+            manifest: "requirements.txt",
+            content: "requests==2.32.0\n",
+            trigger: Some("requests"),
+            globs: &["**/*.py"],
+        },
+        "go" => Ecosystem {
+            manifest: "go.mod",
+            content: "module example.com/fixture\n\ngo 1.22\n\nrequire github.com/gin-gonic/gin v1.10.0\n",
+            trigger: Some("gin"),
+            globs: &["**/*.go"],
+        },
+        "node" | "javascript" | "typescript" | "react" => Ecosystem {
+            manifest: "package.json",
+            content: "{\n  \"name\": \"fixture\",\n  \"version\": \"0.1.0\",\n  \"dependencies\": {\n    \"express\": \"^4.19.0\"\n  }\n}\n",
+            trigger: Some("express"),
+            globs: &["**/*.js", "**/*.ts"],
+        },
+        _ => Ecosystem {
+            manifest: "README.md",
+            content: "Projeto de exemplo onde a skill deve ser recomendada.\n",
+            trigger: None,
+            globs: &["**/*"],
+        },
+    }
+}
+
+/// `clean-code` → `Clean Code`
+fn title_case(slug: &str) -> String {
+    slug.split('-')
+        .filter(|w| !w.is_empty())
+        .map(|w| {
+            let mut chars = w.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Separa `<categoria>/<nome>`, aceitando só minúsculas, dígitos e '-'
+pub fn parse_id(id: &str) -> anyhow::Result<(String, String)> {
+    let valid = |part: &str| {
+        !part.is_empty()
+            && !part.starts_with('-')
+            && part
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    };
+    match id.split_once('/') {
+        Some((category, name)) if valid(category) && valid(name) => {
+            Ok((category.to_string(), name.to_string()))
+        }
+        _ => anyhow::bail!(
+            "❌ ID '{}' inválido. Use <categoria>/<nome> com letras minúsculas, números e '-' (ex: rust/clean-code).",
+            id
+        ),
+    }
+}
+
+fn quoted(values: &[&str]) -> String {
+    let items: Vec<String> = values.iter().map(|v| format!("\"{}\"", v)).collect();
+    format!("[{}]", items.join(", "))
+}
+
+/// Gera o diretório de uma nova skill e devolve os arquivos criados
+pub fn scaffold(dir: &Path, id: &str, premium: bool) -> anyhow::Result<Vec<PathBuf>> {
+    let (category, slug) = parse_id(id)?;
+    if fs::read_dir(dir).is_ok_and(|mut entries| entries.next().is_some()) {
+        anyhow::bail!(
+            "❌ {} já existe e não está vazio; nada foi gerado.",
+            dir.display()
+        );
+    }
+
+    let name = title_case(&slug);
+    let eco = ecosystem(&category);
+    let triggers: Vec<&str> = eco.trigger.into_iter().collect();
+
+    let manifest = format!(
+        r#"# Metadados da skill: viram a entrada do registry.json na publicação
+id = "{id}"
+name = "{name}"
+category = "{category}"
+description = "Uma frase sobre o que a skill ensina ao agente."
+# Nome do arquivo em .cursor/rules
+file_name = "{slug}.mdc"
+premium = {premium}
+# Dependências que fazem o `rustskill audit` recomendar a skill
+triggers = {triggers}
+# Arquivos em que o Cursor aplica a regra
+globs = {globs}
+"#,
+        id = id,
+        name = name,
+        category = title_case(&category),
+        slug = slug,
+        premium = premium,
+        triggers = quoted(&triggers),
+        globs = quoted(eco.globs),
+    );
+
+    let body = format!(
+        "# {name}\n\n\
+         Descreva em linguagem direta como o agente deve se comportar neste projeto.\n\n\
+         ## Regras\n\n\
+         - Uma regra por linha, verificável na revisão de código.\n\
+         - Prefira exemplos concretos a princípios genéricos.\n\n\
+         ## Exemplo\n\n\
+         Mostre um trecho antes/depois que ilustre a regra principal.\n",
+        name = name
+    );
+
+    let fixture = dir.join(FIXTURE_DIR);
+    fs::create_dir_all(&fixture)?;
+    let files = [
+        (dir.join(SKILL_MANIFEST), manifest),
+        (dir.join(SKILL_BODY), body),
+        (fixture.join(eco.manifest), eco.content.to_string()),
+    ];
+    for (path, content) in &files {
+        write_atomic(path, content.as_bytes())?;
+    }
+    Ok(files.into_iter().map(|(path, _)| path).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_id_accepts_only_category_and_slug() {
+        assert_eq!(
+            parse_id("rust/clean-code-2").unwrap(),
+            ("rust".to_string(), "clean-code-2".to_string())
+        );
+        for id in [
+            "rust", "Rust/x", "rust/", "/x", "rust/-x", "rust/a b", "a/b/c",
+        ] {
+            assert!(parse_id(id).is_err(), "{}", id);
+        }
+    }
+
+    #[test]
+    fn split_frontmatter_reads_json_and_comma_globs() {
+        let (globs, body) =
+            split_frontmatter("---\nglobs: [\"**/*.rs\", \"a,b\"]\n---\nUse Result.\n");
+        assert_eq!(globs, ["**/*.rs", "a,b"]);
+        assert_eq!(body, "Use Result.\n");
+
+        let (globs, _) = split_frontmatter("---\nglobs: **/*.rs, \"**/*.toml\" ,\n---\nx");
+        assert_eq!(globs, ["**/*.rs", "**/*.toml"]);
+    }
+
+    #[test]
+    fn split_frontmatter_handles_crlf() {
+        let (globs, body) = split_frontmatter(
+            "---\r\ndescription: X\r\nglobs: **/*.rs\r\n---\r\n\r\nUse Result.\r\n",
+        );
+        assert_eq!(globs, ["**/*.rs"]);
+        assert_eq!(body, "Use Result.\r\n");
+    }
+
+    #[test]
+    fn split_frontmatter_keeps_unclosed_or_missing_header() {
+        let unclosed = "---\nglobs: **/*.rs\nUse Result.\n";
+        assert_eq!(split_frontmatter(unclosed), (Vec::new(), unclosed));
+        let plain = "# X\n---\nUse Result.\n";
+        assert_eq!(split_frontmatter(plain), (Vec::new(), plain));
+    }
+
+    #[test]
+    fn scaffold_generates_a_loadable_skill() {
+        let dir = tempfile::tempdir().unwrap();
+        let skill = dir.path().join("clean-code");
+        let files = scaffold(&skill, "rust/clean-code", false).unwrap();
+        assert_eq!(files.len(), 3);
+        assert!(skill.join(FIXTURE_DIR).join("Cargo.toml").is_file());
+
+        let (manifest, body) = load_skill_dir(&skill).unwrap();
+        assert_eq!(manifest.id, "rust/clean-code");
+        assert_eq!(manifest.name, "Clean Code");
+        assert_eq!(manifest.triggers, ["serde"]);
+        assert!(body.starts_with("# Clean Code\n"));
+    }
+
+    #[test]
+    fn scaffold_refuses_non_empty_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("notes.txt"), "meu").unwrap();
+        assert!(scaffold(dir.path(), "rust/x", false).is_err());
+        assert!(!dir.path().join(SKILL_MANIFEST).exists());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("notes.txt")).unwrap(),
+            "meu"
+        );
+
+        assert!(scaffold(&dir.path().join("y"), "Rust/Y", false).is_err());
+        assert!(!dir.path().join("y").exists());
+    }
+}
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Arquivo versionado no repositório com as recomendações recusadas de propósito
+pub const BASELINE_FILE: &str = "rustskill-baseline.toml";
+
+/// Recomendação aceita como "não instalada" pelo time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Suppression {
+    pub id: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Baseline {
+    #[serde(default)]
+    pub suppress: Vec<Suppression>,
+}
+
+impl Baseline {
+    /// Carrega o baseline em `root`; ausência do arquivo equivale a baseline vazio
+    pub fn load(root: &Path) -> anyhow::Result<Self> {
+        let path = root.join(BASELINE_FILE);
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let baseline: Baseline = toml::from_str(&content)
+            .map_err(|e| anyhow::anyhow!("❌ {} inválido: {}", BASELINE_FILE, e))?;
+
+        for s in &baseline.suppress {
+            if s.reason.trim().is_empty() {
+                anyhow::bail!(
+                    "❌ {}: a supressão de '{}' precisa de uma justificativa (reason).",
+                    BASELINE_FILE,
+                    s.id
+                );
+            }
+        }
+
+        Ok(baseline)
+    }
+
+    pub fn find(&self, id: &str) -> Option<&Suppression> {
+        self.suppress.iter().find(|s| s.id == id)
+    }
+}
@@ -0,0 +1,114 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Diretório local com um JSON por execução do audit
+pub const HISTORY_DIR: &str = ".rustskill/history";
+
+/// Fotografia da governança do projeto em uma execução do audit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub timestamp: DateTime<Utc>,
+    /// Commit (abreviado) do HEAD no momento do audit, se for um repositório git
+    pub commit: Option<String>,
+    pub ecosystems: Vec<String>,
+    pub recommended: Vec<String>,
+    pub installed: Vec<String>,
+    pub missing: Vec<String>,
+    #[serde(default)]
+    pub accepted: Vec<String>,
+}
+
+impl AuditRecord {
+    /// Percentual das recomendações (descontadas as aceitas no baseline) que estão instaladas
+    pub fn coverage(&self) -> u8 {
+        let expected = self.recommended.len().saturating_sub(self.accepted.len());
+        if expected == 0 {
+            return 100;
+        }
+        ((self.installed.len().min(expected) * 100) / expected) as u8
+    }
+
+    /// Registra uma skill instalada pelo `audit --fix` depois da fotografia inicial
+    pub fn mark_installed(&mut self, id: &str) {
+        self.missing.retain(|m| m != id);
+        if !self.installed.iter().any(|i| i == id) {
+            self.installed.push(id.to_string());
+        }
+    }
+}
+
+/// Grava o registro em `root/.rustskill/history/<timestamp>.json`
+pub fn record(root: &Path, record: &AuditRecord) -> anyhow::Result<PathBuf> {
+    let dir = root.join(HISTORY_DIR);
+    fs::create_dir_all(&dir)?;
+
+    let path = dir.join(format!(
+        "{}.json",
+        record.timestamp.format("%Y%m%dT%H%M%S%.3fZ")
+    ));
+    fs::write(&path, serde_json::to_string_pretty(record)?)?;
+    Ok(path)
+}
+
+/// Carrega todo o histórico em ordem cronológica, ignorando arquivos corrompidos
+pub fn load_all(root: &Path) -> anyhow::Result<Vec<AuditRecord>> {
+    let dir = root.join(HISTORY_DIR);
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut records: Vec<AuditRecord> = entries
+        .flatten()
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|e| fs::read_to_string(e.path()).ok())
+        .filter_map(|content| serde_json::from_str(&content).ok())
+        .collect();
+
+    records.sort_by_key(|r| r.timestamp);
+    Ok(records)
+}
+
+/// Commit atual do repositório em `root`, se houver git disponível
+pub fn current_commit(root: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .current_dir(root)
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+    let commit = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!commit.is_empty()).then_some(commit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mark_installed_updates_coverage() {
+        let mut record = AuditRecord {
+            timestamp: Utc::now(),
+            commit: None,
+            ecosystems: vec!["rust".to_string()],
+            recommended: vec!["rust/a".to_string(), "rust/b".to_string()],
+            installed: vec!["rust/a".to_string()],
+            missing: vec!["rust/b".to_string()],
+            accepted: Vec::new(),
+        };
+        assert_eq!(record.coverage(), 50);
+
+        record.mark_installed("rust/b");
+        record.mark_installed("rust/b");
+        assert!(record.missing.is_empty());
+        assert_eq!(record.installed, ["rust/a", "rust/b"]);
+        assert_eq!(record.coverage(), 100);
+    }
+}
@@ -1,51 +1,625 @@
+use crate::client::downloader::SkillPayload;
+use crate::core::lockfile::{LockedSkill, Lockfile};
+use crate::utils::fs::write_atomic;
+use crate::utils::hash::sha256_hex;
 use console::style;
-use std::env;
 use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
-/// Instala a instrução da skill no diretório do Cursor com blindagem de diretórios
-pub fn install_to_cursor(content: &str, file_name: &str, skill_name: &str) -> anyhow::Result<()> {
-    let current_dir = env::current_dir()?;
-    let rules_path = current_dir.join(".cursor").join("rules");
+/// Conteúdo final gravado em `.cursor/rules`: frontmatter do RustSkill + instrução
+pub fn render_managed(payload: &SkillPayload) -> String {
+    let globs = match payload.globs.as_deref() {
+        Some(globs) if !globs.is_empty() => globs.to_vec(),
+        _ => vec!["**/*".to_string()],
+    };
+    format!(
+        "---\ndescription: Skill gerenciada pelo RustSkill: {}\nglobs: {}\n---\n\n{}",
+        payload.name,
+        serde_json::to_string(&globs).unwrap_or_default(),
+        payload.instruction
+    )
+}
 
-    // 2. Criação Robusta: Garante que a estrutura existe antes de gravar
-    if !rules_path.exists() {
-        fs::create_dir_all(&rules_path)?;
-    }
+/// Nomes de dispositivo reservados no Windows, proibidos com qualquer extensão
+const RESERVED_NAMES: &[&str] = &[
+    "con", "prn", "aux", "nul", "com1", "com2", "com3", "com4", "com5", "com6", "com7", "com8",
+    "com9", "lpt1", "lpt2", "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8", "lpt9",
+];
+/// Sufixo da cópia de segurança de um arquivo que não era gerenciado pelo RustSkill
+pub const BACKUP_SUFFIX: &str = ".bak";
+/// Cópias de segurança mantidas por arquivo antes de recusar a troca
+const MAX_BACKUPS: usize = 100;
+/// Limite do nome (sem a extensão), abaixo do máximo de 255 bytes dos sistemas comuns
+const MAX_NAME_LEN: usize = 100;
 
-    let mut safe_name = if file_name.trim().is_empty() || file_name == "null" {
-        skill_name.replace("/", "-").to_lowercase()
+/// Reduz o `file_name` vindo da rede a um único nome `.mdc` seguro dentro de `.cursor/rules`
+pub fn sanitize_file_name(file_name: &str, skill_name: &str) -> anyhow::Result<String> {
+    let raw = if file_name.trim().is_empty() || file_name == "null" {
+        skill_name
     } else {
-        file_name.to_lowercase()
+        file_name
     };
 
-    // Remove caracteres proibidos em sistemas de arquivos
-    safe_name = safe_name.replace(['/', '\\', ':', '*', '?', '"', '<', '>', '|'], "-");
+    // Separadores, caracteres proibidos e de controle viram '-': nunca sobra mais de um componente
+    let mut name: String = raw
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_control() || ['/', '\\', ':', '*', '?', '"', '<', '>', '|'].contains(&c) {
+                '-'
+            } else {
+                c
+            }
+        })
+        .collect();
 
-    // Garante a extensão .mdc
-    if !safe_name.ends_with(".mdc") {
-        safe_name.push_str(".mdc");
+    if let Some(stem) = name.strip_suffix(".mdc") {
+        name = stem.to_string();
     }
+    // Sem '.', '..' ou arquivos ocultos; Windows também ignora pontos e espaços finais
+    let mut name = name
+        .trim_matches(|c: char| c == '.' || c == '-' || c.is_whitespace())
+        .to_string();
 
-    let full_path = rules_path.join(&safe_name);
+    if name.len() > MAX_NAME_LEN {
+        let mut cut = MAX_NAME_LEN;
+        while !name.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        name.truncate(cut);
+        name = name.trim_end_matches(['.', '-', ' ']).to_string();
+    }
 
-    let managed_content = format!(
-        "---\ndescription: Skill gerenciada pelo RustSkill: {}\nglobs: [\"**/*\"]\n---\n\n{}",
-        skill_name, content
-    );
+    if name.is_empty() {
+        anyhow::bail!(
+            "❌ Nome de arquivo inválido recebido para a skill: '{}'",
+            raw
+        );
+    }
+    let device = name.split('.').next().unwrap_or_default();
+    if RESERVED_NAMES.contains(&device) {
+        name = format!("skill-{}", name);
+    }
 
-    fs::write(&full_path, managed_content)?;
+    Ok(format!("{}.mdc", name))
+}
 
-    // 6. LOG DE CONFIRMAÇÃO OBRIGATÓRIO (O seu Debug visual)
-    println!(
-        "{} Skill '{}' injetada com sucesso!",
-        style("🚀").blue(),
-        style(skill_name).cyan()
-    );
-    println!(
-        "   {} Caminho: {}",
-        style("↳").dim(),
-        style(full_path.display()).dim().italic()
-    );
+/// Recusa caminhos que sejam links simbólicos: a escrita nunca pode sair do projeto
+fn reject_symlink(path: &Path) -> anyhow::Result<()> {
+    match fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_symlink() => anyhow::bail!(
+            "❌ {} é um link simbólico; por segurança nada foi gravado.",
+            path.display()
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// `.cursor/rules` canônico e confinado ao projeto, criado se preciso
+pub fn rules_dir(root: &Path) -> anyhow::Result<PathBuf> {
+    let root = root.canonicalize()?;
+    let cursor = root.join(".cursor");
+    let rules = cursor.join("rules");
+
+    reject_symlink(&cursor)?;
+    reject_symlink(&rules)?;
+    fs::create_dir_all(&rules)?;
+
+    let rules = rules.canonicalize()?;
+    if !rules.starts_with(&root) {
+        anyhow::bail!(
+            "❌ {} aponta para fora do projeto; por segurança nada foi gravado.",
+            rules.display()
+        );
+    }
+    Ok(rules)
+}
+
+/// Grava a cópia de segurança sem nunca sobrescrever outra: `x.mdc.bak`, `x.mdc.1.bak`, ...
+fn write_backup(rules_path: &Path, file: &str, content: &[u8]) -> anyhow::Result<PathBuf> {
+    for n in 0..MAX_BACKUPS {
+        let name = match n {
+            0 => format!("{}{}", file, BACKUP_SUFFIX),
+            n => format!("{}.{}{}", file, n, BACKUP_SUFFIX),
+        };
+        let path = rules_path.join(name);
+        // `create_new` também falha em links simbólicos, sem segui-los
+        let mut backup = match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+        {
+            Ok(backup) => backup,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => {
+                anyhow::bail!("❌ Falha ao criar a cópia {}: {}", path.display(), e)
+            }
+        };
+        let written = backup.write_all(content).and_then(|_| backup.sync_all());
+        if let Err(e) = written {
+            let _ = fs::remove_file(&path);
+            anyhow::bail!("❌ Falha ao gravar a cópia {}: {}", path.display(), e);
+        }
+        return Ok(path);
+    }
+    anyhow::bail!(
+        "❌ Já existem {} cópias de {} em {}; apague as antigas antes de continuar.",
+        MAX_BACKUPS,
+        file,
+        rules_path.display()
+    )
+}
+
+/// Skill validada e renderizada, aguardando o commit da transação
+struct Staged {
+    skill_id: String,
+    skill_name: String,
+    file: String,
+    content: String,
+    source: Option<String>,
+}
+
+/// Estado anterior de um arquivo já substituído, para o rollback
+struct Replaced {
+    path: PathBuf,
+    original: Option<Vec<u8>>,
+}
+
+/// Conteúdo existente que não bate com o lock é do usuário e merece backup antes da troca
+fn needs_backup(lock: &Lockfile, file: &str, existing: &[u8]) -> bool {
+    !lock
+        .skills
+        .iter()
+        .any(|s| s.file == file && s.sha256 == sha256_hex(existing))
+}
+
+/// O que o commit faria com um arquivo (usado pelo `--dry-run`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    Create,
+    Update { backup: bool },
+    Unchanged,
+}
 
-    Ok(())
+/// Mudança prevista para uma skill do lote, com o conteúdo antes e depois
+#[derive(Debug, Clone)]
+pub struct Planned {
+    pub skill_id: String,
+    pub path: PathBuf,
+    pub change: Change,
+    pub old: Option<String>,
+    pub new: String,
+}
+
+/// Instalação em lote: ou todas as skills entram em `.cursor/rules`, ou nenhuma
+pub struct Transaction {
+    root: PathBuf,
+    staged: Vec<Staged>,
+}
+
+impl Transaction {
+    pub fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            staged: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.staged.is_empty()
+    }
+
+    /// Valida o nome e prepara o conteúdo; nada é gravado até o `commit`.
+    /// `source` registra no lockfile a origem de skills que não vêm do registry.
+    pub fn stage(
+        &mut self,
+        skill_id: &str,
+        payload: &SkillPayload,
+        source: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let file = sanitize_file_name(&payload.file_name, &payload.name)?;
+        if let Some(other) = self.staged.iter().find(|s| s.file == file) {
+            anyhow::bail!(
+                "❌ '{}' e '{}' gravariam o mesmo arquivo {}.",
+                other.skill_id,
+                skill_id,
+                file
+            );
+        }
+        self.staged.push(Staged {
+            skill_id: skill_id.to_string(),
+            skill_name: payload.name.clone(),
+            file,
+            content: render_managed(payload),
+            source: source.map(str::to_string),
+        });
+        Ok(())
+    }
+
+    /// Prevê o resultado do commit sem criar diretórios nem gravar nada
+    pub fn plan(&self) -> anyhow::Result<Vec<Planned>> {
+        let rules_path = self.root.join(".cursor").join("rules");
+        reject_symlink(&self.root.join(".cursor"))?;
+        reject_symlink(&rules_path)?;
+        let lock = Lockfile::load(&self.root)?;
+
+        let mut planned = Vec::new();
+        for staged in &self.staged {
+            let path = rules_path.join(&staged.file);
+            reject_symlink(&path)?;
+
+            let old = match fs::read(&path) {
+                Ok(bytes) => Some(bytes),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+                Err(e) => return Err(e.into()),
+            };
+            let change = match &old {
+                None => Change::Create,
+                Some(bytes) if bytes == staged.content.as_bytes() => Change::Unchanged,
+                Some(bytes) => Change::Update {
+                    backup: needs_backup(&lock, &staged.file, bytes),
+                },
+            };
+
+            planned.push(Planned {
+                skill_id: staged.skill_id.clone(),
+                path,
+                change,
+                old: old.map(|bytes| String::from_utf8_lossy(&bytes).into_owned()),
+                new: staged.content.clone(),
+            });
+        }
+        Ok(planned)
+    }
+
+    /// Grava todas as skills e o lockfile; qualquer falha restaura os arquivos anteriores
+    pub fn commit(self) -> anyhow::Result<()> {
+        let rules_path = rules_dir(&self.root)?;
+        let mut lock = Lockfile::load(&self.root)?;
+        let mut replaced: Vec<Replaced> = Vec::new();
+        let mut backups: Vec<PathBuf> = Vec::new();
+
+        let result = (|| -> anyhow::Result<()> {
+            for staged in &self.staged {
+                let full_path = rules_path.join(&staged.file);
+                // Confinamento final: o alvo é filho direto de .cursor/rules e não é um link
+                if full_path.parent() != Some(rules_path.as_path()) {
+                    anyhow::bail!("❌ Caminho de destino inválido: {}", full_path.display());
+                }
+                reject_symlink(&full_path)?;
+
+                let original = match fs::read(&full_path) {
+                    Ok(bytes) => Some(bytes),
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+                    Err(e) => return Err(e.into()),
+                };
+
+                // Arquivo do usuário (ou editado por ele) ganha uma cópia .bak antes de ser trocado
+                if let Some(bytes) = &original {
+                    if needs_backup(&lock, &staged.file, bytes) {
+                        let backup = write_backup(&rules_path, &staged.file, bytes)?;
+                        backups.push(backup.clone());
+                        println!(
+                            "   {} Cópia do arquivo anterior: {}",
+                            style("↳").dim(),
+                            style(backup.display()).dim()
+                        );
+                    }
+                }
+
+                replaced.push(Replaced {
+                    path: full_path.clone(),
+                    original,
+                });
+                write_atomic(&full_path, staged.content.as_bytes())?;
+            }
+
+            // Registra no lockfile para a detecção de drift do audit
+            for staged in &self.staged {
+                lock.upsert(LockedSkill {
+                    id: staged.skill_id.clone(),
+                    file: staged.file.clone(),
+                    sha256: sha256_hex(staged.content.as_bytes()),
+                    source: staged.source.clone(),
+                });
+            }
+            lock.save(&self.root)
+        })();
+
+        if let Err(e) = result {
+            for entry in replaced.iter().rev() {
+                let _ = match &entry.original {
+                    Some(bytes) => write_atomic(&entry.path, bytes),
+                    None => fs::remove_file(&entry.path).map_err(Into::into),
+                };
+            }
+            // Os originais voltaram para o lugar: as cópias deste lote não servem mais
+            for backup in &backups {
+                let _ = fs::remove_file(backup);
+            }
+            anyhow::bail!(
+                "{}\n{} Instalação desfeita: nenhuma skill do lote foi aplicada.",
+                e,
+                style("↩️").yellow()
+            );
+        }
+
+        // 6. LOG DE CONFIRMAÇÃO OBRIGATÓRIO (O seu Debug visual)
+        for staged in &self.staged {
+            println!(
+                "{} Skill '{}' injetada com sucesso!",
+                style("🚀").blue(),
+                style(&staged.skill_name).cyan()
+            );
+            println!(
+                "   {} Caminho: {}",
+                style("↳").dim(),
+                style(rules_path.join(&staged.file).display())
+                    .dim()
+                    .italic()
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload(file_name: &str) -> SkillPayload {
+        SkillPayload {
+            name: "Clean Code".to_string(),
+            instruction: "Seja claro.".to_string(),
+            file_name: file_name.to_string(),
+            signature: None,
+            globs: None,
+        }
+    }
+
+    fn sanitize(file_name: &str) -> anyhow::Result<String> {
+        sanitize_file_name(file_name, "Clean Code")
+    }
+
+    #[test]
+    fn sanitize_keeps_names_inside_rules_dir() {
+        assert_eq!(sanitize("../../x").unwrap(), "x.mdc");
+        assert_eq!(sanitize("..\\..\\x.mdc").unwrap(), "x.mdc");
+        assert_eq!(sanitize("/etc/passwd").unwrap(), "etc-passwd.mdc");
+        assert_eq!(sanitize(".hidden").unwrap(), "hidden.mdc");
+        assert_eq!(sanitize("rust-clean.mdc").unwrap(), "rust-clean.mdc");
+        assert!(sanitize("..").is_err());
+        assert!(sanitize("./.").is_err());
+    }
+
+    #[test]
+    fn sanitize_renames_windows_devices() {
+        assert_eq!(sanitize("con.mdc").unwrap(), "skill-con.mdc");
+        assert_eq!(sanitize("LPT1").unwrap(), "skill-lpt1.mdc");
+        assert_eq!(sanitize("aux.txt").unwrap(), "skill-aux.txt.mdc");
+        assert_eq!(sanitize("console").unwrap(), "console.mdc");
+    }
+
+    #[test]
+    fn sanitize_truncates_multibyte_names_on_char_boundary() {
+        let name = sanitize(&"é".repeat(300)).unwrap();
+        let stem = name.strip_suffix(".mdc").unwrap();
+        assert!(stem.len() <= MAX_NAME_LEN);
+        assert!(stem.chars().all(|c| c == 'é'));
+
+        let name = sanitize(&format!("{}.mdc", "🦀".repeat(80))).unwrap();
+        assert!(name.len() <= MAX_NAME_LEN + ".mdc".len());
+    }
+
+    #[test]
+    fn sanitize_replaces_control_characters() {
+        assert_eq!(sanitize("a\nb\0c\u{1b}").unwrap(), "a-b-c.mdc");
+        assert_eq!(sanitize("x\r\n").unwrap(), "x.mdc");
+    }
+
+    #[test]
+    fn sanitize_falls_back_to_skill_name() {
+        assert_eq!(sanitize("null").unwrap(), "clean code.mdc");
+        assert_eq!(sanitize("   ").unwrap(), "clean code.mdc");
+        assert_eq!(sanitize_file_name("", "../x").unwrap(), "x.mdc");
+        assert!(sanitize_file_name("", "").is_err());
+        assert!(sanitize_file_name("null", "..").is_err());
+    }
+
+    #[test]
+    fn rules_dir_is_created_inside_project() {
+        let project = tempfile::tempdir().unwrap();
+        let rules = rules_dir(project.path()).unwrap();
+        assert!(rules.starts_with(project.path().canonicalize().unwrap()));
+        assert!(rules.is_dir());
+    }
+
+    #[test]
+    fn commit_writes_only_inside_rules_dir() {
+        let project = tempfile::tempdir().unwrap();
+        let mut tx = Transaction::new(project.path());
+        tx.stage("rust/x", &payload("../../x.mdc"), None).unwrap();
+        tx.commit().unwrap();
+
+        let written = project.path().join(".cursor/rules/x.mdc");
+        assert!(fs::read_to_string(written)
+            .unwrap()
+            .ends_with("Seja claro."));
+        assert!(!project.path().join("x.mdc").exists());
+    }
+
+    #[test]
+    fn plan_predicts_each_change_without_writing() {
+        let project = tempfile::tempdir().unwrap();
+        let mut tx = Transaction::new(project.path());
+        tx.stage("rust/a", &payload("a.mdc"), None).unwrap();
+        assert_eq!(tx.plan().unwrap()[0].change, Change::Create);
+        assert!(!project.path().join(".cursor").exists());
+
+        tx.stage("rust/b", &payload("b.mdc"), None).unwrap();
+        tx.stage("rust/c", &payload("c.mdc"), None).unwrap();
+        tx.commit().unwrap();
+        let edited = project.path().join(".cursor/rules/c.mdc");
+        fs::write(&edited, "editado pelo usuário").unwrap();
+
+        let mut tx = Transaction::new(project.path());
+        tx.stage("rust/a", &payload("a.mdc"), None).unwrap();
+        for file in ["b.mdc", "c.mdc", "d.mdc"] {
+            let newer = SkillPayload {
+                instruction: "Seja ainda mais claro.".to_string(),
+                ..payload(file)
+            };
+            tx.stage(&format!("rust/{}", &file[..1]), &newer, None)
+                .unwrap();
+        }
+        let changes: Vec<Change> = tx.plan().unwrap().into_iter().map(|p| p.change).collect();
+        assert_eq!(
+            changes,
+            [
+                Change::Unchanged,
+                Change::Update { backup: false },
+                Change::Update { backup: true },
+                Change::Create,
+            ]
+        );
+        assert_eq!(fs::read_to_string(edited).unwrap(), "editado pelo usuário");
+        assert_eq!(rules_entries(project.path()), ["a.mdc", "b.mdc", "c.mdc"]);
+    }
+
+    fn rules_entries(project: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(project.join(".cursor/rules"))
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn backups_never_overwrite_each_other() {
+        let project = tempfile::tempdir().unwrap();
+        let rules = project.path().join(".cursor/rules");
+        fs::create_dir_all(&rules).unwrap();
+
+        for user_content in ["primeira versão", "segunda versão"] {
+            fs::write(rules.join("x.mdc"), user_content).unwrap();
+            let mut tx = Transaction::new(project.path());
+            tx.stage("rust/x", &payload("x.mdc"), None).unwrap();
+            tx.commit().unwrap();
+        }
+
+        assert_eq!(
+            fs::read_to_string(rules.join("x.mdc.bak")).unwrap(),
+            "primeira versão"
+        );
+        assert_eq!(
+            fs::read_to_string(rules.join("x.mdc.1.bak")).unwrap(),
+            "segunda versão"
+        );
+    }
+
+    #[test]
+    fn failed_batch_removes_its_backups() {
+        let project = tempfile::tempdir().unwrap();
+        let rules = project.path().join(".cursor/rules");
+        fs::create_dir_all(&rules).unwrap();
+        fs::write(rules.join("x.mdc"), "do usuário").unwrap();
+        fs::write(rules.join("x.mdc.bak"), "cópia antiga").unwrap();
+        // Um diretório no lugar do segundo arquivo derruba o lote depois do backup do primeiro
+        fs::create_dir(rules.join("y.mdc")).unwrap();
+
+        let mut tx = Transaction::new(project.path());
+        tx.stage("rust/x", &payload("x.mdc"), None).unwrap();
+        tx.stage("rust/y", &payload("y.mdc"), None).unwrap();
+        assert!(tx.commit().is_err());
+
+        assert_eq!(
+            fs::read_to_string(rules.join("x.mdc")).unwrap(),
+            "do usuário"
+        );
+        assert_eq!(
+            fs::read_to_string(rules.join("x.mdc.bak")).unwrap(),
+            "cópia antiga"
+        );
+        assert_eq!(
+            rules_entries(project.path()),
+            ["x.mdc", "x.mdc.bak", "y.mdc"]
+        );
+    }
+
+    #[cfg(unix)]
+    mod symlinks {
+        use super::*;
+        use std::os::unix::fs::symlink;
+
+        fn assert_untouched(outside: &Path) {
+            let entries: Vec<_> = fs::read_dir(outside)
+                .unwrap()
+                .map(|e| e.unwrap().file_name())
+                .collect();
+            assert!(entries.is_empty(), "gravou fora do projeto: {:?}", entries);
+        }
+
+        fn install(project: &Path) -> anyhow::Result<()> {
+            let mut tx = Transaction::new(project);
+            tx.stage("rust/x", &payload("x.mdc"), None)?;
+            tx.plan()?;
+            tx.commit()
+        }
+
+        #[test]
+        fn refuses_cursor_symlink() {
+            let project = tempfile::tempdir().unwrap();
+            let outside = tempfile::tempdir().unwrap();
+            symlink(outside.path(), project.path().join(".cursor")).unwrap();
+
+            assert!(install(project.path()).is_err());
+            assert!(rules_dir(project.path()).is_err());
+            assert_untouched(outside.path());
+        }
+
+        #[test]
+        fn refuses_rules_symlink() {
+            let project = tempfile::tempdir().unwrap();
+            let outside = tempfile::tempdir().unwrap();
+            fs::create_dir(project.path().join(".cursor")).unwrap();
+            symlink(outside.path(), project.path().join(".cursor/rules")).unwrap();
+
+            assert!(install(project.path()).is_err());
+            assert!(rules_dir(project.path()).is_err());
+            assert_untouched(outside.path());
+        }
+
+        #[test]
+        fn refuses_target_symlink() {
+            let project = tempfile::tempdir().unwrap();
+            let outside = tempfile::tempdir().unwrap();
+            let rules = project.path().join(".cursor/rules");
+            fs::create_dir_all(&rules).unwrap();
+            // Link pendente: seguir o link criaria o arquivo fora do projeto
+            symlink(outside.path().join("x.mdc"), rules.join("x.mdc")).unwrap();
+
+            assert!(install(project.path()).is_err());
+            assert_untouched(outside.path());
+        }
+
+        #[test]
+        fn refuses_target_symlink_to_existing_file() {
+            let project = tempfile::tempdir().unwrap();
+            let outside = tempfile::tempdir().unwrap();
+            let victim = outside.path().join("victim.txt");
+            fs::write(&victim, "original").unwrap();
+            let rules = project.path().join(".cursor/rules");
+            fs::create_dir_all(&rules).unwrap();
+            symlink(&victim, rules.join("x.mdc")).unwrap();
+
+            let mut tx = Transaction::new(project.path());
+            tx.stage("rust/x", &payload("x.mdc"), None).unwrap();
+            assert!(tx.commit().is_err());
+            assert_eq!(fs::read_to_string(&victim).unwrap(), "original");
+            assert_eq!(fs::read_dir(outside.path()).unwrap().count(), 1);
+        }
+    }
 }
@@ -0,0 +1,59 @@
+use crate::utils::fs::write_atomic;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Lockfile versionado com o que o RustSkill instalou em `.cursor/rules`
+pub const LOCK_FILE: &str = "rustskill.lock";
+
+/// Skill instalada e o hash do arquivo gerenciado no momento da instalação
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockedSkill {
+    pub id: String,
+    /// Nome do arquivo dentro de `.cursor/rules`
+    pub file: String,
+    /// SHA-256 do conteúdo gerenciado (frontmatter + instrução)
+    pub sha256: String,
+    /// Origem fora do registry (caminho local, `git+...` ou URL), se houver
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Lockfile {
+    #[serde(default, rename = "skill")]
+    pub skills: Vec<LockedSkill>,
+}
+
+impl Lockfile {
+    /// Carrega o lockfile em `root`; ausência do arquivo equivale a lockfile vazio
+    pub fn load(root: &Path) -> anyhow::Result<Self> {
+        let path = root.join(LOCK_FILE);
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+
+        toml::from_str(&content).map_err(|e| anyhow::anyhow!("❌ {} inválido: {}", LOCK_FILE, e))
+    }
+
+    pub fn save(&self, root: &Path) -> anyhow::Result<()> {
+        let content = format!(
+            "# Gerado pelo RustSkill. Não edite manualmente.\n\n{}",
+            toml::to_string_pretty(self)?
+        );
+        write_atomic(&root.join(LOCK_FILE), content.as_bytes())
+    }
+
+    pub fn get(&self, id: &str) -> Option<&LockedSkill> {
+        self.skills.iter().find(|s| s.id == id)
+    }
+
+    /// Insere ou substitui a entrada da skill, mantendo a ordem por ID
+    pub fn upsert(&mut self, entry: LockedSkill) {
+        self.skills.retain(|s| s.id != entry.id);
+        self.skills.push(entry);
+        self.skills.sort_by(|a, b| a.id.cmp(&b.id));
+    }
+}
@@ -1 +1,10 @@
+pub mod audit;
+pub mod authoring;
+pub mod baseline;
+pub mod guard;
+pub mod history;
 pub mod installer;
+pub mod lint;
+pub mod lockfile;
+pub mod policy;
+pub mod scanner;
@@ -0,0 +1,191 @@
+use crate::client::downloader::SkillEntry;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Política versionada no repositório
+pub const POLICY_FILE: &str = "rustskill.policy.toml";
+/// Política extra, somada às demais; nunca substitui a política do sistema
+pub const POLICY_ENV: &str = "RUSTSKILL_POLICY";
+
+/// Política da organização instalada na máquina (MDM, imagem base, etc.)
+fn system_policy_path() -> PathBuf {
+    if cfg!(windows) {
+        let base = std::env::var("ProgramData").unwrap_or_else(|_| r"C:\ProgramData".into());
+        PathBuf::from(base).join("rustskill").join("policy.toml")
+    } else {
+        PathBuf::from("/etc/rustskill/policy.toml")
+    }
+}
+
+/// Regras de uma política; listas `allowed_*` vazias não restringem nada
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Policy {
+    /// URLs de registry.json de onde é permitido instalar
+    #[serde(default)]
+    pub allowed_registries: Vec<String>,
+    /// IDs permitidos; `rust/*` libera a categoria inteira
+    #[serde(default)]
+    pub allowed_skills: Vec<String>,
+    #[serde(default)]
+    pub denied_skills: Vec<String>,
+    /// Categorias banidas (comparação sem diferenciar maiúsculas)
+    #[serde(default)]
+    pub denied_categories: Vec<String>,
+}
+
+impl Policy {
+    /// Motivo da negação, ou `None` se a skill é permitida
+    fn deny_reason(&self, entry: &SkillEntry) -> Option<String> {
+        if !self.allowed_registries.is_empty()
+            && !self
+                .allowed_registries
+                .iter()
+                .any(|r| r.trim_end_matches('/') == entry.registry.trim_end_matches('/'))
+        {
+            return Some(format!(
+                "o registry {} não está em allowed_registries",
+                entry.registry
+            ));
+        }
+        if let Some(pattern) = self.denied_skills.iter().find(|p| matches_id(p, &entry.id)) {
+            return Some(format!("'{}' está em denied_skills", pattern));
+        }
+        if !self.allowed_skills.is_empty()
+            && !self.allowed_skills.iter().any(|p| matches_id(p, &entry.id))
+        {
+            return Some("a skill não está em allowed_skills".to_string());
+        }
+        if self
+            .denied_categories
+            .iter()
+            .any(|c| c.eq_ignore_ascii_case(&entry.category))
+        {
+            return Some(format!("a categoria '{}' é proibida", entry.category));
+        }
+        None
+    }
+}
+
+/// Padrão exato ou prefixo terminado em `*`
+fn matches_id(pattern: &str, id: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => id.starts_with(prefix),
+        None => pattern == id,
+    }
+}
+
+/// Políticas em vigor (sistema e repositório); uma skill precisa passar em todas
+#[derive(Debug, Default)]
+pub struct Policies {
+    loaded: Vec<(PathBuf, Policy)>,
+}
+
+impl Policies {
+    /// Carrega a política do sistema, a de `RUSTSKILL_POLICY` e a do repositório em `root`.
+    /// Sistema e repositório são opcionais; a da variável, se definida, precisa existir.
+    pub fn load(root: &Path) -> anyhow::Result<Self> {
+        let extra = std::env::var_os(POLICY_ENV)
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from);
+        Self::load_from(system_policy_path(), extra, root)
+    }
+
+    fn load_from(system: PathBuf, extra: Option<PathBuf>, root: &Path) -> anyhow::Result<Self> {
+        let mut sources = vec![(system, false)];
+        sources.extend(extra.map(|path| (path, true)));
+        sources.push((root.join(POLICY_FILE), false));
+
+        let mut loaded = Vec::new();
+        for (path, required) in sources {
+            let content = match fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound && !required => continue,
+                Err(e) => anyhow::bail!(
+                    "❌ Não foi possível ler a política {}: {}",
+                    path.display(),
+                    e
+                ),
+            };
+            let policy: Policy = toml::from_str(&content)
+                .map_err(|e| anyhow::anyhow!("❌ Política {} inválida: {}", path.display(), e))?;
+            loaded.push((path, policy));
+        }
+        Ok(Self { loaded })
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.loaded.iter().map(|(path, _)| path.as_path())
+    }
+
+    /// Falha com o motivo da primeira política que negar a instalação
+    pub fn check(&self, entry: &SkillEntry) -> anyhow::Result<()> {
+        for (path, policy) in &self.loaded {
+            if let Some(reason) = policy.deny_reason(entry) {
+                anyhow::bail!(
+                    "❌ Instalação de '{}' negada pela política {}: {}.",
+                    entry.id,
+                    path.display(),
+                    reason
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str) -> SkillEntry {
+        SkillEntry {
+            id: id.to_string(),
+            name: id.to_string(),
+            category: "rust".to_string(),
+            url: String::new(),
+            premium: false,
+            triggers: None,
+            sha256: None,
+            size: None,
+            registry: "https://example.com/registry.json".to_string(),
+        }
+    }
+
+    #[test]
+    fn extra_policy_does_not_replace_system_policy() {
+        let dir = tempfile::tempdir().unwrap();
+        let system = dir.path().join("system.toml");
+        let extra = dir.path().join("extra.toml");
+        fs::write(&system, "denied_skills = [\"rust/banned\"]\n").unwrap();
+        fs::write(&extra, "denied_skills = [\"rust/other\"]\n").unwrap();
+
+        let policies = Policies::load_from(system, Some(extra), dir.path()).unwrap();
+        assert_eq!(policies.paths().count(), 2);
+        assert!(policies.check(&entry("rust/banned")).is_err());
+        assert!(policies.check(&entry("rust/other")).is_err());
+        assert!(policies.check(&entry("rust/clean-code")).is_ok());
+    }
+
+    #[test]
+    fn missing_extra_policy_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = Policies::load_from(
+            dir.path().join("system.toml"),
+            Some(dir.path().join("nonexistent.toml")),
+            dir.path(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("nonexistent.toml"), "{}", err);
+    }
+
+    #[test]
+    fn missing_system_and_repo_policies_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let policies =
+            Policies::load_from(dir.path().join("system.toml"), None, dir.path()).unwrap();
+        assert_eq!(policies.paths().count(), 0);
+        assert!(policies.check(&entry("rust/clean-code")).is_ok());
+    }
+}
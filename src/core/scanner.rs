@@ -0,0 +1,292 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Diretórios que nunca entram no Raio-X (dependências vendorizadas e artefatos de build)
+const IGNORED_DIRS: [&str; 6] = ["target", "node_modules", ".git", "venv", "dist", "build"];
+
+/// Extensões que passam pelo Deep Scan de imports
+const CODE_EXTENSIONS: [&str; 6] = ["rs", "py", "go", "js", "ts", "tsx"];
+
+/// Extensões que identificam cada ecossistema suportado
+const ECOSYSTEMS: [(&str, &[&str]); 4] = [
+    ("Rust", &["rs"]),
+    ("Python", &["py"]),
+    ("Go", &["go"]),
+    ("Node", &["js", "ts", "tsx"]),
+];
+
+/// Quantas linhas do topo de cada arquivo são analisadas (onde ficam os imports)
+const IMPORT_SCAN_LINES: usize = 100;
+
+/// Onde uma dependência foi encontrada durante o scan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Evidence {
+    /// `import x`, `from x import`, `use x::y` ou `import "x"` em um arquivo de código
+    Import { path: PathBuf, line: usize },
+    /// Entrada em um manifesto (`Cargo.toml`, `package.json`, `requirements.txt`, `go.mod`)
+    Manifest {
+        manifest: PathBuf,
+        section: String,
+        dev: bool,
+    },
+}
+
+impl fmt::Display for Evidence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Evidence::Import { path, line } => write!(f, "import em {}:{}", path.display(), line),
+            Evidence::Manifest {
+                manifest, section, ..
+            } => write!(f, "manifesto {} [{}]", manifest.display(), section),
+        }
+    }
+}
+
+/// Resultado do scan poliglota de um projeto
+#[derive(Debug, Default)]
+pub struct ProjectScan {
+    /// Extensão (minúscula) -> arquivos que a possuem
+    pub extensions: HashMap<String, Vec<PathBuf>>,
+    /// Dependência (minúscula) -> todas as evidências encontradas
+    pub dependencies: HashMap<String, Vec<Evidence>>,
+}
+
+impl ProjectScan {
+    /// Ecossistemas com pelo menos um arquivo de código no projeto
+    pub fn ecosystems(&self) -> Vec<String> {
+        ECOSYSTEMS
+            .iter()
+            .filter(|(_, exts)| exts.iter().any(|e| self.extensions.contains_key(*e)))
+            .map(|(name, _)| name.to_string())
+            .collect()
+    }
+
+    pub fn extension_files(&self, ext: &str) -> &[PathBuf] {
+        self.extensions.get(ext).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn dependency_evidence(&self, dep: &str) -> &[Evidence] {
+        self.dependencies.get(dep).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Quantidade de arquivos distintos que importam a dependência
+    pub fn import_files(&self, dep: &str) -> usize {
+        self.dependency_evidence(dep)
+            .iter()
+            .filter_map(|e| match e {
+                Evidence::Import { path, .. } => Some(path),
+                _ => None,
+            })
+            .collect::<BTreeSet<_>>()
+            .len()
+    }
+
+    fn add(&mut self, dep: &str, evidence: Evidence) {
+        let dep = dep.to_lowercase();
+        if dep.is_empty() {
+            return;
+        }
+        self.dependencies.entry(dep).or_default().push(evidence);
+    }
+}
+
+/// Escaneia o projeto em `root`: extensões, imports e manifestos
+pub fn scan_project(root: &Path) -> ProjectScan {
+    let mut scan = ProjectScan::default();
+
+    // 1. Scan de Arquivos (Extensões & Raio-X de Código v0.4.0)
+    for entry in WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|e| {
+            let name = e.file_name().to_string_lossy();
+            !IGNORED_DIRS.contains(&name.as_ref())
+        })
+        .flatten()
+    {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let path = entry.path();
+        let rel = relative(root, path);
+
+        let Some(ext) = path.extension().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        let ext_lower = ext.to_lowercase();
+        scan.extensions
+            .entry(ext_lower.clone())
+            .or_default()
+            .push(rel.clone());
+
+        // RAIO-X: Se for arquivo de código, faz o Deep Scan de Imports
+        if CODE_EXTENSIONS.contains(&ext_lower.as_str()) {
+            if let Ok(code) = fs::read_to_string(path) {
+                for (idx, line) in code.lines().take(IMPORT_SCAN_LINES).enumerate() {
+                    if let Some(dep) = parse_import(line) {
+                        scan.add(
+                            &dep,
+                            Evidence::Import {
+                                path: rel.clone(),
+                                line: idx + 1,
+                            },
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    // 2. Scan Híbrido de Manifestos (Otimizado v0.3.0)
+    scan_cargo(root, &mut scan);
+    scan_package_json(root, &mut scan);
+    scan_requirements(root, &mut scan);
+    scan_go_mod(root, &mut scan);
+
+    scan
+}
+
+/// Extrai o nome da dependência de uma linha de import, se houver
+fn parse_import(line: &str) -> Option<String> {
+    let line = line.trim();
+
+    // Padrão Go: import "x"
+    if line.starts_with("import \"") {
+        let dep = line
+            .trim_start_matches("import")
+            .replace([' ', '"', ';'], "");
+        return dep.rsplit('/').next().map(str::to_string);
+    }
+
+    // Padrão Python/JS/TS: import x ou from x import
+    if line.starts_with("import ") || line.starts_with("from ") {
+        let dep = line.split_whitespace().nth(1)?;
+        return dep
+            .split('.')
+            .next()
+            .map(|d| d.replace([';', '"', '\''], ""));
+    }
+
+    // Padrão Rust: use x::y
+    if line.starts_with("use ") {
+        let dep = line.split_whitespace().nth(1)?;
+        return dep.split("::").next().map(|d| d.replace(';', ""));
+    }
+
+    None
+}
+
+fn scan_cargo(root: &Path, scan: &mut ProjectScan) {
+    let manifest = PathBuf::from("Cargo.toml");
+    let Ok(content) = fs::read_to_string(root.join(&manifest)) else {
+        return;
+    };
+    let Ok(cargo) = content.parse::<toml::Value>() else {
+        return;
+    };
+    for sec in ["dependencies", "dev-dependencies"] {
+        if let Some(deps) = cargo.get(sec).and_then(|d| d.as_table()) {
+            for name in deps.keys() {
+                scan.add(
+                    name,
+                    Evidence::Manifest {
+                        manifest: manifest.clone(),
+                        section: sec.to_string(),
+                        dev: sec == "dev-dependencies",
+                    },
+                );
+            }
+        }
+    }
+}
+
+fn scan_package_json(root: &Path, scan: &mut ProjectScan) {
+    let manifest = PathBuf::from("package.json");
+    let Ok(content) = fs::read_to_string(root.join(&manifest)) else {
+        return;
+    };
+    let Ok(pkg) = serde_json::from_str::<serde_json::Value>(&content) else {
+        return;
+    };
+    for key in ["dependencies", "devDependencies"] {
+        if let Some(deps) = pkg.get(key).and_then(|d| d.as_object()) {
+            for name in deps.keys() {
+                scan.add(
+                    name,
+                    Evidence::Manifest {
+                        manifest: manifest.clone(),
+                        section: key.to_string(),
+                        dev: key == "devDependencies",
+                    },
+                );
+            }
+        }
+    }
+}
+
+fn scan_requirements(root: &Path, scan: &mut ProjectScan) {
+    let manifest = PathBuf::from("requirements.txt");
+    let Ok(content) = fs::read_to_string(root.join(&manifest)) else {
+        return;
+    };
+    for line in content
+        .lines()
+        .filter(|l| !l.trim().is_empty() && !l.trim().starts_with('#'))
+    {
+        if let Some(dep) = line
+            .split(|c: char| !c.is_alphanumeric() && c != '-' && c != '_')
+            .next()
+        {
+            scan.add(
+                dep.trim(),
+                Evidence::Manifest {
+                    manifest: manifest.clone(),
+                    section: "requirements".to_string(),
+                    dev: false,
+                },
+            );
+        }
+    }
+}
+
+fn scan_go_mod(root: &Path, scan: &mut ProjectScan) {
+    let manifest = PathBuf::from("go.mod");
+    let Ok(content) = fs::read_to_string(root.join(&manifest)) else {
+        return;
+    };
+    for line in content
+        .lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty() && !l.starts_with("//"))
+    {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let full_path = if parts.len() >= 2 && parts[0] == "require" {
+            parts[1].to_lowercase()
+        } else if !parts.is_empty()
+            && line.contains('/')
+            && !["module", "go", "replace"].contains(&parts[0])
+        {
+            parts[0].to_lowercase()
+        } else {
+            continue;
+        };
+
+        let evidence = Evidence::Manifest {
+            manifest: manifest.clone(),
+            section: "require".to_string(),
+            dev: false,
+        };
+        scan.add(&full_path, evidence.clone());
+        if let Some(short_name) = full_path.rsplit('/').next() {
+            if short_name != full_path {
+                scan.add(short_name, evidence);
+            }
+        }
+    }
+}
+
+fn relative(root: &Path, path: &Path) -> PathBuf {
+    path.strip_prefix(root).unwrap_or(path).to_path_buf()
+}
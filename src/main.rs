@@ -2,49 +2,546 @@ use clap::{Parser, Subcommand};
 use comfy_table::Table;
 use console::style;
 use indicatif::{ProgressBar, ProgressStyle};
-use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
 use std::fs;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
-use walkdir::WalkDir;
 
-use rustskill::client::downloader;
-use rustskill::core::installer;
+use rustskill::client::downloader::AccountInfo;
+use rustskill::client::profile::{Config, Session, DEFAULT_PROFILE};
+use rustskill::client::source::{self, SkillSource};
+use rustskill::client::updater::{self, Channel};
+use rustskill::client::{credentials, downloader, oauth, signature};
+use rustskill::core::audit::RuleState;
+use rustskill::core::baseline::{self, Baseline};
+use rustskill::core::guard::{self, GuardConfig, GuardMode};
+use rustskill::core::history::{self, AuditRecord};
+use rustskill::core::lockfile::Lockfile;
+use rustskill::core::policy::Policies;
+use rustskill::core::{audit, authoring, installer, lint, scanner};
+use rustskill::utils::diff;
+use rustskill::utils::hash::sha256_hex;
 
 #[derive(Parser)]
 #[command(name = "rustskill", version = env!("CARGO_PKG_VERSION"), about = "AI Skills Platform - Governança de Código com IA")]
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Lê o token premium deste arquivo (precede RUSTSKILL_TOKEN e o login salvo)
+    #[arg(long, global = true, value_name = "ARQUIVO")]
+    token_file: Option<PathBuf>,
+
+    /// Perfil de autenticação (cliente/assinatura) usado nesta execução
+    #[arg(long, global = true, value_name = "NOME")]
+    profile: Option<String>,
+
+    /// Permite enviar tokens sobre HTTP sem TLS (somente para servidores de teste locais)
+    #[arg(long, global = true)]
+    insecure: bool,
 }
 
-#[derive(Serialize, Deserialize, Debug, Default)]
-struct Config {
-    token: Option<String>,
+/// Move o token em texto puro do confy para o chaveiro (ou arquivo 0600)
+fn migrate_plaintext_token() {
+    let Ok(mut cfg) = Config::load() else {
+        return;
+    };
+    let Some(token) = cfg.token.take() else {
+        return;
+    };
+
+    match credentials::store_token(&credentials::account(DEFAULT_PROFILE, None), &token) {
+        Ok(location) => {
+            if let Err(e) = cfg.store() {
+                println!(
+                    "{} Token migrado, mas a config antiga não foi limpa: {}",
+                    style("⚠️").yellow(),
+                    e
+                );
+                return;
+            }
+            println!(
+                "{} Token premium migrado do arquivo de configuração para: {}",
+                style("🔐").cyan(),
+                location
+            );
+        }
+        Err(e) => println!(
+            "{} Não foi possível migrar o token em texto puro: {}",
+            style("⚠️").yellow(),
+            e
+        ),
+    }
 }
 
 #[derive(Subcommand)]
 enum Commands {
     /// Lista o marketplace de skills (Global Registry)
     List,
-    /// Instala uma skill usando o Alias (ex: rust/clean-code)
-    Add { alias: String },
+    /// Instala uma skill pelo alias (ex: rust/clean-code), caminho local (./, /, ~ ou .mdc),
+    /// git+https://host/repo#caminho@ref ou URL de um .mdc
+    Add {
+        alias: String,
+        /// Mostra o que mudaria em .cursor/rules, sem gravar nada
+        #[arg(long)]
+        dry_run: bool,
+    },
     /// Escaneia o projeto e sugere as skills de vanguarda necessárias
+    #[command(args_conflicts_with_subcommands = true)]
     Audit {
+        #[command(subcommand)]
+        action: Option<AuditAction>,
         #[arg(long)]
         fix: bool,
+        /// Com --fix, mostra o que seria instalado, sem gravar nada
+        #[arg(long, requires = "fix")]
+        dry_run: bool,
+        /// Falha (exit 1) se houver skills recomendadas ausentes; ideal para CI
+        #[arg(long, conflicts_with = "fix")]
+        check: bool,
+        /// Compara as skills instaladas com o conteúdo publicado hoje (detecta desatualizadas)
+        #[arg(long)]
+        refresh: bool,
+        /// Oculta recomendações com confiança abaixo deste valor (0-100)
+        #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=100))]
+        min_confidence: u8,
     },
     /// Atualiza o rustskill para a versão mais recente
-    Upgrade,
+    Upgrade {
+        /// Só verifica; sai com código 10 se houver atualização
+        #[arg(long, conflicts_with = "version")]
+        check: bool,
+        /// Instala uma versão específica (ex: 0.3.1), inclusive anterior à atual
+        #[arg(long, value_name = "VERSÃO")]
+        version: Option<String>,
+        /// Canal de releases: stable ou beta (pré-releases)
+        #[arg(long)]
+        channel: Option<Channel>,
+        /// Volta para o binário anterior à última atualização
+        #[arg(long, conflicts_with_all = ["check", "version", "channel"])]
+        rollback: bool,
+    },
     /// Login com Token Premium para acessar skills restritas
-    Login { token: String },
+    Login {
+        /// Token premium (prefira --with-token: argumentos vazam no histórico e no `ps`)
+        #[arg(conflicts_with = "with_token")]
+        token: Option<String>,
+        /// Lê o token da entrada padrão
+        #[arg(long)]
+        with_token: bool,
+        /// Guarda o token como credencial exclusiva deste host (registry privado)
+        #[arg(long)]
+        host: Option<String>,
+        /// Login pelo navegador (OAuth device code), sem colar tokens
+        #[arg(long, conflicts_with_all = ["token", "with_token", "host"])]
+        device: bool,
+    },
+    /// Remove o token premium guardado neste computador
+    Logout {
+        /// Remove apenas a credencial deste host
+        #[arg(long)]
+        host: Option<String>,
+    },
+    /// Gerencia perfis de autenticação (um por cliente/assinatura)
+    Profile {
+        #[command(subcommand)]
+        action: ProfileAction,
+    },
+    /// Mostra a conta, o plano e a validade do token guardado
+    Whoami,
+    /// Diagnóstico da autenticação
+    Auth {
+        #[command(subcommand)]
+        action: AuthAction,
+    },
+    /// Explica por que uma skill foi (ou não) recomendada pelo audit
+    Explain { alias: String },
     /// Mostra detalhes técnicos de uma skill específica
     Info { alias: String },
+    /// Diff entre a skill instalada e o conteúdo publicado hoje
+    Diff { alias: String },
+    /// Procura instruções perigosas (prompt injection, curl | sh, segredos) em um arquivo de skill
+    Scan { file: PathBuf },
+    /// Valida registry.json, arquivos .mdc e diretórios de skill
+    Lint {
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        /// Relatório em JSON (stdout), para CI e editores
+        #[arg(long)]
+        json: bool,
+        /// Não testa se as URLs absolutas do registry respondem
+        #[arg(long)]
+        offline: bool,
+    },
+    /// Imprime o JSON Schema do registry.json ou da resposta da API premium
+    Schema {
+        #[arg(value_parser = ["registry", "payload"], default_value = "registry")]
+        kind: String,
+    },
+    /// Cria o esqueleto de uma nova skill (ex: rust/clean-code)
+    New {
+        id: String,
+        /// Diretório de destino (padrão: ./<categoria>/<nome>)
+        #[arg(long)]
+        dir: Option<PathBuf>,
+        /// Marca a skill como premium nos metadados
+        #[arg(long)]
+        premium: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum AuditAction {
+    /// Mostra a evolução da cobertura de governança entre os audits
+    History {
+        /// Quantidade de audits mais recentes exibidos
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+}
+
+#[derive(Subcommand)]
+enum AuthAction {
+    /// Onde o token está guardado e se ele ainda é aceito pela API
+    Status,
+}
+
+#[derive(Subcommand)]
+enum ProfileAction {
+    /// Lista os perfis configurados
+    List,
+    /// Cria ou atualiza um perfil
+    Add {
+        name: String,
+        /// URL base da API premium deste perfil
+        #[arg(long)]
+        api_url: Option<String>,
+        /// URL de um registry.json (repita para vários)
+        #[arg(long = "registry")]
+        registries: Vec<String>,
+    },
+    /// Remove o perfil e as credenciais dele
+    Remove { name: String },
+    /// Define o perfil usado quando --profile não é informado
+    Use { name: String },
+    /// Exige assinatura minisign de um registry, confiando na chave pública informada
+    Trust {
+        name: String,
+        /// Chave pública minisign (base64, a linha de dados do arquivo .pub)
+        key: String,
+        /// Registry assinado com esta chave (padrão: Catálogo Global)
+        #[arg(long, default_value = downloader::BASE_REGISTRY_URL)]
+        registry: String,
+        /// Remove a chave em vez de adicioná-la
+        #[arg(long)]
+        remove: bool,
+    },
+}
+
+/// Comandos de perfil rodam antes da sessão existir (o perfil pode nem ter sido criado)
+fn run_profile(action: &ProfileAction) -> anyhow::Result<()> {
+    let mut cfg = Config::load()?;
+
+    match action {
+        ProfileAction::List => {
+            let active = cfg.active_profile(None);
+            let mut table = Table::new();
+            table.set_header(vec!["Perfil", "API", "Registries", "Hosts", "Assinatura"]);
+
+            let mut names: Vec<String> = cfg.profiles.keys().cloned().collect();
+            if !cfg.profiles.contains_key(DEFAULT_PROFILE) {
+                names.insert(0, DEFAULT_PROFILE.to_string());
+            }
+            for name in names {
+                let profile = cfg.profiles.get(&name).cloned().unwrap_or_default();
+                let label = if name == active {
+                    style(format!("★ {}", name)).green().bold().to_string()
+                } else {
+                    style(name).cyan().to_string()
+                };
+                table.add_row(vec![
+                    label,
+                    profile
+                        .api_base_url
+                        .unwrap_or_else(|| downloader::API_BASE_URL.to_string()),
+                    if profile.registries.is_empty() {
+                        "Catálogo Global".to_string()
+                    } else {
+                        profile.registries.join("\n")
+                    },
+                    profile.hosts.join("\n"),
+                    profile
+                        .trusted_keys
+                        .iter()
+                        .map(|(registry, keys)| format!("{} ({} chave(s))", registry, keys.len()))
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                ]);
+            }
+            println!("{table}");
+        }
+        ProfileAction::Add {
+            name,
+            api_url,
+            registries,
+        } => {
+            if let Some(url) = api_url {
+                reqwest::Url::parse(url)
+                    .map_err(|e| anyhow::anyhow!("❌ URL da API inválida '{}': {}", url, e))?;
+                if !url.starts_with("https://") {
+                    println!(
+                        "{} A API '{}' não usa HTTPS; o token só será enviado com --insecure.",
+                        style("⚠️").yellow(),
+                        url
+                    );
+                }
+            }
+            let profile = cfg.profiles.entry(name.clone()).or_default();
+            if api_url.is_some() {
+                profile.api_base_url = api_url.clone();
+            }
+            if !registries.is_empty() {
+                profile.registries = registries.clone();
+            }
+            cfg.store()?;
+            println!(
+                "{} Perfil '{}' salvo. Faça login com {}",
+                style("✅").green(),
+                style(name).cyan(),
+                style(format!("rustskill login --profile {} --with-token", name)).green()
+            );
+        }
+        ProfileAction::Remove { name } => {
+            let Some(profile) = cfg.profiles.remove(name) else {
+                println!("{} Perfil '{}' não existe.", style("❌").red(), name);
+                return Ok(());
+            };
+            let account = credentials::account(name, None);
+            credentials::delete_token(&account)?;
+            credentials::delete_token(&credentials::refresh_account(&account))?;
+            for host in &profile.hosts {
+                credentials::delete_token(&credentials::account(name, Some(host)))?;
+            }
+            if cfg.default_profile.as_deref() == Some(name.as_str()) {
+                cfg.default_profile = None;
+            }
+            cfg.store()?;
+            println!(
+                "{} Perfil '{}' e suas credenciais removidos.",
+                style("👋").cyan(),
+                name
+            );
+        }
+        ProfileAction::Use { name } => {
+            if name != DEFAULT_PROFILE && !cfg.profiles.contains_key(name) {
+                println!("{} Perfil '{}' não existe.", style("❌").red(), name);
+                return Ok(());
+            }
+            cfg.default_profile = Some(name.clone());
+            cfg.store()?;
+            println!(
+                "{} Perfil ativo: {}",
+                style("✅").green(),
+                style(name).cyan()
+            );
+        }
+        ProfileAction::Trust {
+            name,
+            key,
+            registry,
+            remove,
+        } => {
+            if name != DEFAULT_PROFILE && !cfg.profiles.contains_key(name) {
+                println!("{} Perfil '{}' não existe.", style("❌").red(), name);
+                return Ok(());
+            }
+            let key = key.trim().to_string();
+            let profile = cfg.profiles.entry(name.clone()).or_default();
+
+            if *remove {
+                if let Some(keys) = profile.trusted_keys.get_mut(registry) {
+                    keys.retain(|k| *k != key);
+                    if keys.is_empty() {
+                        profile.trusted_keys.remove(registry);
+                    }
+                }
+                cfg.store()?;
+                println!(
+                    "{} Chave removida de {}.",
+                    style("👋").cyan(),
+                    style(registry).cyan()
+                );
+                return Ok(());
+            }
+
+            signature::check_key(&key)?;
+            let keys = profile.trusted_keys.entry(registry.clone()).or_default();
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+            cfg.store()?;
+            println!(
+                "{} {} agora exige assinatura válida (registry e skills).",
+                style("🔏").green(),
+                style(registry).cyan()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Roda o guard sobre a skill antes de gravá-la; no modo `block`, qualquer achado impede a instalação
+fn guard_skill(id: &str, instruction: &str, config: &GuardConfig) -> anyhow::Result<()> {
+    if config.mode == GuardMode::Off {
+        return Ok(());
+    }
+    let findings = guard::inspect(instruction, config);
+    if findings.is_empty() {
+        return Ok(());
+    }
+
+    println!(
+        "{} Conteúdo suspeito em {}:",
+        style("🛡️").yellow(),
+        style(id).cyan()
+    );
+    for finding in &findings {
+        println!("   {} {}", style("↳").dim(), finding);
+    }
+
+    if config.mode == GuardMode::Block {
+        anyhow::bail!(
+            "❌ Skill '{}' bloqueada pelo guard ({} achado(s)). Nada foi instalado.",
+            id,
+            findings.len()
+        );
+    }
+    Ok(())
+}
+
+/// Diff unificado colorido: adições em verde, remoções em vermelho
+fn print_diff(diff: &str) {
+    for line in diff.lines() {
+        if line.starts_with("+++") || line.starts_with("---") {
+            println!("{}", style(line).bold());
+        } else if line.starts_with('+') {
+            println!("{}", style(line).green());
+        } else if line.starts_with('-') {
+            println!("{}", style(line).red());
+        } else if line.starts_with("@@") {
+            println!("{}", style(line).cyan());
+        } else {
+            println!("{}", line);
+        }
+    }
+}
+
+/// Falha no histórico vira aviso: não deve derrubar o audit
+fn save_history(record: &AuditRecord) {
+    if let Err(e) = history::record(Path::new("."), record) {
+        println!(
+            "{} Não foi possível salvar o histórico: {}",
+            style("⚠️").yellow(),
+            e
+        );
+    }
+}
+
+/// Resumo do `--dry-run`: o que cada skill do lote faria em .cursor/rules
+fn print_plan(plan: &[installer::Planned]) {
+    println!(
+        "\n{} Dry-run: nada foi gravado. Mudanças previstas:",
+        style("🔎").cyan()
+    );
+    for item in plan {
+        let label = match item.change {
+            installer::Change::Create => style("➕ criar".to_string()).green(),
+            installer::Change::Update { backup: true } => {
+                style("✏️ substituir (com .bak)".to_string()).yellow()
+            }
+            installer::Change::Update { backup: false } => {
+                style("✏️ atualizar".to_string()).yellow()
+            }
+            installer::Change::Unchanged => style("✔ sem mudanças".to_string()).dim(),
+        };
+        let path = item.path.strip_prefix(".").unwrap_or(&item.path);
+        println!("{} {} ({})", label, path.display(), item.skill_id);
+        if item.change != installer::Change::Unchanged {
+            print_diff(&diff::unified_diff(
+                item.old.as_deref().unwrap_or_default(),
+                &item.new,
+                &format!("a/{}", path.display()),
+                &format!("b/{}", path.display()),
+            ));
+        }
+    }
+}
+
+/// Detalhes da conta devolvidos por `/auth/validate`
+fn print_account(account: &AccountInfo) {
+    println!(
+        "   {} Conta: {}",
+        style("↳").dim(),
+        style(account.identity.as_deref().unwrap_or("desconhecida")).cyan()
+    );
+    println!(
+        "   {} Plano: {}",
+        style("↳").dim(),
+        style(account.plan.as_deref().unwrap_or("desconhecido")).yellow()
+    );
+    match account.expires_at {
+        Some(exp) if account.is_expired() => println!(
+            "   {} Expirou em: {}",
+            style("↳").dim(),
+            style(exp.format("%Y-%m-%d %H:%M UTC")).red()
+        ),
+        Some(exp) => println!(
+            "   {} Expira em: {}",
+            style("↳").dim(),
+            exp.format("%Y-%m-%d %H:%M UTC")
+        ),
+        None => println!("   {} Expira em: sem expiração", style("↳").dim()),
+    }
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
+    migrate_plaintext_token();
+    let token_file = cli.token_file.as_deref();
+
+    if let Commands::Profile { action } = &cli.command {
+        return run_profile(action);
+    }
+    let mut session = Session::load(cli.profile.as_deref(), token_file)?;
+    session.allow_insecure = cli.insecure;
+    if cli.insecure {
+        println!(
+            "{} --insecure: tokens podem trafegar sem HTTPS.",
+            style("⚠️").yellow()
+        );
+    }
+    let config = Config::load().unwrap_or_default();
+    let guard_config = config.guard.clone().with_env()?;
+
+    // Aviso de nova versão: lê só o cache; a consulta diária roda em segundo plano
+    let notify = config.update.notify_enabled() && !matches!(cli.command, Commands::Upgrade { .. });
+    if notify {
+        if let Some(latest) = updater::cached_update(&config.update) {
+            if console::Term::stderr().is_term() {
+                eprintln!(
+                    "{} Nova versão disponível: {} → {}. Rode {} (desative com {}=1)",
+                    style("📦").cyan(),
+                    env!("CARGO_PKG_VERSION"),
+                    style(&latest).green(),
+                    style("rustskill upgrade").green(),
+                    updater::NO_UPDATE_CHECK_ENV
+                );
+            }
+        }
+    }
+    // Vive até o fim do comando, quando espera brevemente pela consulta terminar
+    let _update_check = notify.then(|| updater::refresh_cache_in_background(&config.update));
 
     match &cli.command {
         Commands::List => {
@@ -52,7 +549,7 @@ async fn main() -> anyhow::Result<()> {
                 "{} Consultando o Marketplace Global...",
                 style("🔍").yellow()
             );
-            let registry = downloader::fetch_registry().await?;
+            let registry = downloader::fetch_registry(&session).await?;
 
             let mut table = Table::new();
             table.set_header(vec!["Categoria", "Skill Alias (ID)", "Acesso", "Status"]);
@@ -74,32 +571,28 @@ async fn main() -> anyhow::Result<()> {
             println!("{table}");
         }
 
-        Commands::Add { alias } => {
-            let registry = downloader::fetch_registry().await?;
-            let skill_entry = registry.iter().find(|s| &s.id == alias);
-
-            match skill_entry {
-                Some(entry) => {
-                    // Carregamos a config aqui para ter o token disponível
-                    let cfg: Config = confy::load("rustskill", None).unwrap_or_default();
+        Commands::Add { alias, dry_run } => {
+            let (skill_id, payload, origin) = match SkillSource::parse(alias)? {
+                SkillSource::Registry(_) => {
+                    let registry = downloader::fetch_registry(&session).await?;
+                    let Some(entry) = registry.iter().find(|s| &s.id == alias) else {
+                        println!("{} Skill '{}' não encontrada.", style("❌").red(), alias);
+                        return Ok(());
+                    };
+                    Policies::load(Path::new("."))?.check(entry)?;
 
                     // --- LÓGICA PREMIUM ---
                     if entry.premium {
-                        match &cfg.token {
-                            Some(token) => {
-                                println!("{} Validando acesso premium...", style("🔑").cyan());
-                                if !downloader::validate_token(token).await? {
-                                    println!("{} Token inválido ou expirado.", style("❌").red());
-                                    return Ok(());
-                                }
-                            }
-                            None => {
-                                println!(
-                                    "{} Skill Premium! Faça login primeiro.",
-                                    style("❌").red()
-                                );
-                                return Ok(());
-                            }
+                        if session.token.is_none() {
+                            println!("{} Skill Premium! Faça login primeiro.", style("❌").red());
+                            return Ok(());
+                        }
+
+                        println!("{} Validando acesso premium...", style("🔑").cyan());
+                        let account = oauth::validate_with_refresh(&mut session).await?;
+                        if account.is_none_or(|a| a.is_expired()) {
+                            println!("{} Token inválido ou expirado.", style("❌").red());
+                            return Ok(());
                         }
                     }
 
@@ -114,21 +607,91 @@ async fn main() -> anyhow::Result<()> {
                     pb.enable_steady_tick(Duration::from_millis(80));
 
                     // --- AQUI ESTÁ A CORREÇÃO: Passamos o token como segundo argumento ---
-                    let skill_content = downloader::fetch_skill(&entry.id, cfg.token).await?;
+                    let payload = downloader::fetch_skill(&session, &entry.id).await?;
                     pb.finish_and_clear();
-
-                    installer::install_to_cursor(
-                        &skill_content.instruction,
-                        &skill_content.file_name,
-                        &skill_content.name,
-                    )?;
+                    (entry.id.clone(), payload, None)
                 }
-                None => {
-                    println!("{} Skill '{}' não encontrada.", style("❌").red(), alias);
+                external => {
+                    println!(
+                        "{} Buscando skill fora do registry: {}...",
+                        style("📂").cyan(),
+                        style(alias).cyan()
+                    );
+                    let resolved = source::resolve(&session, &external).await?;
+                    Policies::load(Path::new("."))?.check(&resolved.entry)?;
+                    (resolved.entry.id, resolved.payload, Some(alias.as_str()))
                 }
+            };
+
+            guard_skill(&skill_id, &payload.instruction, &guard_config)?;
+            let mut tx = installer::Transaction::new(Path::new("."));
+            tx.stage(&skill_id, &payload, origin)?;
+            if *dry_run {
+                print_plan(&tx.plan()?);
+            } else {
+                tx.commit()?;
+            }
+        }
+        Commands::Audit {
+            action: Some(AuditAction::History { limit }),
+            ..
+        } => {
+            let records = history::load_all(Path::new("."))?;
+            if records.is_empty() {
+                println!(
+                    "{} Nenhum audit registrado em {}. Rode {} primeiro.",
+                    style("ℹ").blue(),
+                    history::HISTORY_DIR,
+                    style("rustskill audit").green()
+                );
+                return Ok(());
+            }
+
+            let mut table = Table::new();
+            table.set_header(vec![
+                "Data",
+                "Commit",
+                "Ecossistemas",
+                "Recomendadas",
+                "Instaladas",
+                "Ausentes",
+                "Cobertura",
+                "Tendência",
+            ]);
+
+            let start = records.len().saturating_sub(*limit);
+            let mut previous = start.checked_sub(1).map(|i| records[i].coverage());
+            for record in &records[start..] {
+                let coverage = record.coverage();
+                let trend = match previous {
+                    Some(p) if coverage > p => style(format!("▲ +{}", coverage - p)).green(),
+                    Some(p) if coverage < p => style(format!("▼ -{}", p - coverage)).red(),
+                    Some(_) => style("= 0".to_string()).dim(),
+                    None => style("-".to_string()).dim(),
+                };
+                previous = Some(coverage);
+
+                table.add_row(vec![
+                    record.timestamp.format("%Y-%m-%d %H:%M").to_string(),
+                    record.commit.clone().unwrap_or_else(|| "-".to_string()),
+                    record.ecosystems.join(", "),
+                    record.recommended.len().to_string(),
+                    record.installed.len().to_string(),
+                    record.missing.len().to_string(),
+                    style(format!("{}%", coverage)).yellow().to_string(),
+                    trend.to_string(),
+                ]);
             }
+            println!("{table}");
         }
-        Commands::Audit { fix } => {
+        Commands::Audit {
+            action: None,
+            fix,
+            dry_run,
+            check,
+            refresh,
+            min_confidence,
+        } => {
             println!(
                 "{} Analisando ecossistemas Python, Go, Rust e Node...",
                 style("🔍").yellow()
@@ -139,297 +702,198 @@ async fn main() -> anyhow::Result<()> {
             pb.set_message("Escaneando DNA poliglota do projeto...");
             pb.enable_steady_tick(Duration::from_millis(80));
 
-            let mut extensions = HashSet::new();
-            let mut dependencies = HashSet::new();
+            let scan = scanner::scan_project(Path::new("."));
 
-            // 1. Scan de Arquivos (Extensões & Raio-X de Código v0.4.0)
-            for entry in WalkDir::new(".")
+            pb.finish_and_clear();
+
+            let rules_dir = Path::new(".cursor/rules");
+            let lock = Lockfile::load(Path::new("."))?;
+            let baseline = Baseline::load(Path::new("."))?;
+            let registry = downloader::fetch_registry(&session).await?;
+
+            // Recomendações ordenadas pela confiança, descartando evidências fracas
+            let recommendations: Vec<_> = audit::recommend(&registry, &scan)
                 .into_iter()
-                .filter_entry(|e| {
-                    let name = e.file_name().to_string_lossy();
-                    !["target", "node_modules", ".git", "venv", "dist", "build"]
-                        .contains(&name.as_ref())
-                })
-                .flatten()
-            {
-                if entry.file_type().is_file() {
-                    let path = entry.path();
-
-                    // Captura Extensão
-                    if let Some(ext) = path.extension().and_then(|s| s.to_str()) {
-                        let ext_lower = ext.to_lowercase();
-                        extensions.insert(ext_lower.clone());
-
-                        // RAIO-X: Se for arquivo de código, faz o Deep Scan de Imports
-                        if ["rs", "py", "go", "js", "ts", "tsx"].contains(&ext_lower.as_str()) {
-                            if let Ok(code) = fs::read_to_string(path) {
-                                for line in code.lines().take(100) {
-                                    // Analisa as primeiras 100 linhas (onde ficam os imports)
-                                    let line = line.trim();
-
-                                    // Padrão Python/JS/TS: import x ou from x import
-                                    if line.starts_with("import ") || line.starts_with("from ") {
-                                        let parts: Vec<&str> = line.split_whitespace().collect();
-                                        if parts.len() >= 2 {
-                                            let dep = parts[1]
-                                                .split('.')
-                                                .next()
-                                                .unwrap()
-                                                .replace([';', '"', '\''], "");
-                                            dependencies.insert(dep.to_lowercase());
-                                        }
-                                    }
-                                    // Padrão Rust: use x::y
-                                    else if line.starts_with("use ") {
-                                        if let Some(dep) = line.split_whitespace().nth(1) {
-                                            let dep =
-                                                dep.split("::").next().unwrap().replace(';', "");
-                                            dependencies.insert(dep.to_lowercase());
-                                        }
-                                    }
-                                    // Padrão Go: import "x"
-                                    else if line.starts_with("import \"") {
-                                        let dep = line.replace(
-                                            ['i', 'm', 'p', 'o', 'r', 't', ' ', '"', ';'],
-                                            "",
-                                        );
-                                        if let Some(short_name) = dep.split('/').last() {
-                                            dependencies.insert(short_name.to_lowercase());
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+                .filter(|r| r.confidence() >= *min_confidence)
+                .collect();
 
-            // 2. Scan Híbrido de Manifestos (Otimizado v0.3.0)
+            let mut table = Table::new();
+            table.set_header(vec![
+                "Categoria",
+                "Skill Recomendada",
+                "Confiança",
+                "Motivo",
+                "Status",
+            ]);
 
-            // --- RUST (Cargo.toml) ---
-            if let Ok(content) = fs::read_to_string("Cargo.toml") {
-                if let Ok(cargo) = content.parse::<toml::Value>() {
-                    for sec in ["dependencies", "dev-dependencies"] {
-                        if let Some(deps) = cargo.get(sec).and_then(|d| d.as_table()) {
-                            for name in deps.keys() {
-                                dependencies.insert(name.to_lowercase());
-                            }
-                        }
-                    }
-                }
-            }
+            let mut pending = Vec::new();
+            let mut record = AuditRecord {
+                timestamp: chrono::Utc::now(),
+                commit: history::current_commit(Path::new(".")),
+                ecosystems: scan.ecosystems(),
+                recommended: Vec::new(),
+                installed: Vec::new(),
+                missing: Vec::new(),
+                accepted: Vec::new(),
+            };
+            for rec in &recommendations {
+                let mut state = audit::rule_state(rules_dir, &lock, &rec.skill.id);
 
-            // --- JS/TS (package.json) ---
-            if let Ok(content) = fs::read_to_string("package.json") {
-                if let Ok(pkg) = serde_json::from_str::<serde_json::Value>(&content) {
-                    for key in ["dependencies", "devDependencies"] {
-                        if let Some(deps) = pkg.get(key).and_then(|d| d.as_object()) {
-                            for (name, _) in deps {
-                                dependencies.insert(name.to_lowercase());
+                // Drift remoto: compara o hash do lock com o conteúdo publicado hoje
+                if *refresh && state == RuleState::Installed {
+                    if let Some(locked) = lock.get(&rec.skill.id) {
+                        match downloader::fetch_skill(&session, &rec.skill.id).await {
+                            Ok(payload) => {
+                                let fresh = installer::render_managed(&payload);
+                                if sha256_hex(fresh.as_bytes()) != locked.sha256 {
+                                    state = RuleState::Stale;
+                                }
                             }
+                            Err(e) => println!(
+                                "{} Não foi possível verificar {}: {}",
+                                style("⚠️").yellow(),
+                                rec.skill.id,
+                                e
+                            ),
                         }
                     }
                 }
-            }
 
-            // --- PYTHON (requirements.txt) ---
-            if let Ok(content) = fs::read_to_string("requirements.txt") {
-                for line in content
-                    .lines()
-                    .filter(|l| !l.trim().is_empty() && !l.trim().starts_with('#'))
-                {
-                    let dep = line
-                        .splitn(2, |c: char| !c.is_alphanumeric() && c != '-' && c != '_')
-                        .next()
-                        .unwrap()
-                        .trim();
-                    dependencies.insert(dep.to_lowercase());
-                }
-            }
+                let suppression = baseline.find(&rec.skill.id);
+                let status = match state {
+                    RuleState::Installed => style("✅ Protegido".to_string()).green(),
+                    RuleState::Untracked => style("✅ Protegido (sem lock)".to_string()).yellow(),
+                    RuleState::Modified => style("✏️ Modificado".to_string()).yellow(),
+                    RuleState::Stale => style("🕰️ Desatualizado".to_string()).yellow(),
+                    RuleState::Missing => match suppression {
+                        Some(s) => style(format!("🤝 Aceito: {}", s.reason)).blue(),
+                        None => style("❌ Ausente".to_string()).red(),
+                    },
+                };
 
-            // --- GO (go.mod) ---
-            if let Ok(content) = fs::read_to_string("go.mod") {
-                for line in content
-                    .lines()
-                    .map(|l| l.trim())
-                    .filter(|l| !l.is_empty() && !l.starts_with("//"))
+                if state.needs_install() && !(state == RuleState::Missing && suppression.is_some())
                 {
-                    let parts: Vec<&str> = line.split_whitespace().collect();
-                    if parts.len() >= 2 && parts[0] == "require" {
-                        let full_path = parts[1].to_lowercase();
-                        dependencies.insert(full_path.clone());
-
-                        if let Some(short_name) = full_path.split('/').last() {
-                            dependencies.insert(short_name.to_string());
-                        }
-                    } else if parts.len() >= 1
-                        && line.contains('/')
-                        && !["module", "go", "replace"].contains(&parts[0])
-                    {
-                        let full_path = parts[0].to_lowercase();
-                        dependencies.insert(full_path.clone());
-                        if let Some(short_name) = full_path.split('/').last() {
-                            dependencies.insert(short_name.to_string());
-                        }
-                    }
-                }
-            }
-
-            pb.finish_and_clear();
-
-            // 3. Mapeamento de Skills Instaladas
-            let mut installed_skills = HashSet::new();
-            if let Ok(entries) = fs::read_dir(".cursor/rules") {
-                for entry in entries.flatten() {
-                    if let Some(name) = entry.file_name().to_str() {
-                        installed_skills.insert(name.replace(".mdc", ""));
-                    }
+                    pending.push(rec);
                 }
-            }
-
-            // --- CORREÇÃO AQUI: Definimos o registry antes de usar no loop ---
-            let registry = downloader::fetch_registry().await?;
 
-            let mut table = Table::new();
-            table.set_header(vec!["Categoria", "Skill Recomendada", "Motivo", "Status"]);
-
-            let mut count_missing = 0;
-            // Agora o 'registry' existe e o loop abaixo vai funcionar
-            for skill in &registry {
-                let mut should_recommend = false;
-                let mut reasons = Vec::new();
-
-                let id_lower = skill.id.to_lowercase();
-
-                // 1. Checagem por Triggers Específicos
-                if let Some(triggers) = &skill.triggers {
-                    for trigger in triggers {
-                        let t_lower = trigger.to_lowercase();
-                        // Checa se a dependência existe ou se um arquivo com esse nome/extensão existe
-                        if dependencies.contains(&t_lower) || extensions.contains(&t_lower) {
-                            should_recommend = true;
-                            reasons.push(format!("Gatilho '{}' detectado", trigger));
-                        }
-                    }
+                let id = rec.skill.id.clone();
+                record.recommended.push(id.clone());
+                match (state, suppression) {
+                    (RuleState::Missing, Some(_)) => record.accepted.push(id),
+                    (RuleState::Missing, None) => record.missing.push(id),
+                    _ => record.installed.push(id),
                 }
 
-                // 2. Checagem por Ecossistema
-                if !should_recommend {
-                    if id_lower.contains("rust") && extensions.contains("rs") {
-                        should_recommend = true;
-                        reasons.push("Ecossistema Rust detectado".to_string());
-                    } else if id_lower.contains("python") && extensions.contains("py") {
-                        should_recommend = true;
-                        reasons.push("Ecossistema Python detectado".to_string());
-                    } else if id_lower.contains("go") && extensions.contains("go") {
-                        should_recommend = true;
-                        reasons.push("Ecossistema Go detectado".to_string());
-                    }
-                }
+                table.add_row(vec![
+                    style(&rec.skill.category).magenta().to_string(),
+                    style(&rec.skill.id).cyan().bold().to_string(),
+                    style(format!("{}%", rec.confidence())).yellow().to_string(),
+                    style(rec.reasons()).dim().to_string(),
+                    status.to_string(),
+                ]);
+            }
 
-                if should_recommend {
-                    let file_id = skill.id.replace("/", "-");
-                    let status = if installed_skills.contains(&file_id) {
-                        style("✅ Protegido").green().to_string()
-                    } else {
-                        count_missing += 1;
-                        style("❌ Ausente").red().to_string()
-                    };
+            println!("\n{table}");
 
-                    table.add_row(vec![
-                        style(&skill.category).magenta().to_string(),
-                        style(&skill.id).cyan().bold().to_string(),
-                        style(reasons.join(", ")).dim().to_string(),
-                        status,
-                    ]);
-                }
+            // Dry-run não deixa rastro em disco, nem no histórico; com --fix a fotografia
+            // espera a instalação para registrar a cobertura alcançada
+            let fixing = *fix && !*check && !pending.is_empty();
+            if !*dry_run && !fixing {
+                save_history(&record);
             }
 
-            println!("\n{table}");
+            // Arquivos gerenciados cuja skill saiu do registry
+            for orphan in audit::orphaned(&lock, &registry) {
+                println!(
+                    "{} Órfão: {} ({}) não existe mais no registry.",
+                    style("👻").yellow(),
+                    style(&orphan.id).cyan(),
+                    rules_dir.join(&orphan.file).display()
+                );
+            }
 
-            if count_missing > 0 {
-                if *fix {
-                    println!(
-                        "\n{} Iniciando Auto-Cura de vanguarda...",
-                        style("🛠️").cyan()
-                    );
+            if pending.is_empty() {
+                println!(
+                    "\n{} Nenhuma skill recomendada ausente ou alterada.",
+                    style("✨").yellow()
+                );
+            } else if *check {
+                println!(
+                    "\n{} Check falhou: {} skills recomendadas ausentes ou alteradas (aceite-as em {} ou rode {}).",
+                    style("❌").red(),
+                    pending.len(),
+                    style(baseline::BASELINE_FILE).cyan(),
+                    style("rustskill audit --fix").green()
+                );
+                std::process::exit(1);
+            } else if *fix {
+                println!(
+                    "\n{} Iniciando Auto-Cura de vanguarda...",
+                    style("🛠️").cyan()
+                );
+                // Qualquer saída do --fix, inclusive por erro, deixa a fotografia no histórico
+                let fixed: anyhow::Result<Vec<String>> = async {
+                    let policies = Policies::load(Path::new("."))?;
+                    for path in policies.paths() {
+                        println!(
+                            "{} Política em vigor: {}",
+                            style("📜").blue(),
+                            path.display()
+                        );
+                    }
 
                     println!(
                         "{} Debug: {} extensões e {} dependências mapeadas.",
                         style("ℹ").blue(),
-                        extensions.len(),
-                        dependencies.len()
+                        scan.extensions.len(),
+                        scan.dependencies.len()
                     );
 
-                    let _ = fs::create_dir_all(".cursor/rules");
-                    let cfg: Config = confy::load("rustskill", None).unwrap_or_default();
-
-                    for skill in &registry {
-                        let file_id = skill.id.replace("/", "-");
-
-                        if installed_skills.contains(&file_id) {
+                    // Baixa e valida tudo antes de gravar: uma falha no meio não deixa o projeto pela metade
+                    let mut tx = installer::Transaction::new(Path::new("."));
+                    let mut fixed = Vec::new();
+                    for rec in &pending {
+                        let skill = rec.skill;
+                        if let Err(e) = policies.check(skill) {
+                            println!("{}", style(e).red());
                             continue;
                         }
+                        println!("{} Baixando skill: {}...", style("⏳").blue(), skill.id);
 
-                        let mut should_install = false;
-                        let id_l = skill.id.to_lowercase();
-
-                        // 1. Checagem de Extensões
-                        if (id_l.contains("rust") && extensions.contains("rs"))
-                            || (id_l.contains("python") && extensions.contains("py"))
-                            || (id_l.contains("go") && extensions.contains("go"))
-                        {
-                            should_install = true;
-                        }
-
-                        // 2. Checagem de Triggers
-                        if !should_install {
-                            if let Some(triggers) = &skill.triggers {
-                                for t in triggers {
-                                    if dependencies.contains(&t.to_lowercase()) {
-                                        should_install = true;
-                                        break;
-                                    }
-                                }
-                            }
-                        }
-
-                        if should_install {
-                            println!("{} Baixando skill: {}...", style("⏳").blue(), skill.id);
-
-                            match downloader::fetch_skill(&skill.id, cfg.token.clone()).await {
-                                Ok(content) => {
-                                    if let Err(e) = installer::install_to_cursor(
-                                        &content.instruction,
-                                        &content.file_name,
-                                        &content.name,
-                                    ) {
-                                        println!(
-                                            "{} Erro ao instalar {}: {}",
-                                            style("❌").red(),
-                                            skill.id,
-                                            e
-                                        );
-                                    }
-                                }
-                                Err(e) => {
-                                    println!(
-                                        "{} Erro ao baixar {}: {}",
-                                        style("❌").red(),
-                                        skill.id,
-                                        e
-                                    );
-                                }
-                            }
-                        }
+                        let content = downloader::fetch_skill(&session, &skill.id).await?;
+                        guard_skill(&skill.id, &content.instruction, &guard_config)?;
+                        tx.stage(&skill.id, &content, None)?;
+                        fixed.push(skill.id.clone());
                     }
+                    if tx.is_empty() {
+                        println!(
+                            "\n{} Nenhuma skill permitida para instalar.",
+                            style("ℹ").blue()
+                        );
+                        return Ok(Vec::new());
+                    }
+                    if *dry_run {
+                        print_plan(&tx.plan()?);
+                        return Ok(Vec::new());
+                    }
+                    tx.commit()?;
                     println!("\n{} Projeto blindado com sucesso!", style("✨").yellow());
+                    Ok(fixed)
+                }
+                .await;
+
+                if !*dry_run {
+                    for id in fixed.iter().flatten() {
+                        record.mark_installed(id);
+                    }
+                    save_history(&record);
                 }
+                fixed?;
             } else {
                 println!(
                     "\n{} Diagnóstico: {} vulnerabilidades encontradas.",
                     style("⚠️").yellow(),
-                    count_missing
+                    pending.len()
                 );
                 println!(
                     "Rode {} para auto-cura imediata.",
@@ -437,8 +901,99 @@ async fn main() -> anyhow::Result<()> {
                 );
             }
         }
+        Commands::Explain { alias } => {
+            let registry = downloader::fetch_registry(&session).await?;
+            let Some(skill) = registry.iter().find(|s| &s.id == alias) else {
+                println!("{} Skill '{}' não encontrada.", style("❌").red(), alias);
+                return Ok(());
+            };
+
+            let scan = scanner::scan_project(Path::new("."));
+
+            println!(
+                "\n{} Raio-X da recomendação: {}",
+                style("🔬").cyan(),
+                style(alias).bold().yellow()
+            );
+
+            let triggers = skill.triggers.as_deref().unwrap_or_default();
+            if triggers.is_empty() {
+                println!(
+                    "{} Nenhum gatilho cadastrado no registry.",
+                    style("ℹ").blue()
+                );
+            }
+
+            let mut trigger_matched = false;
+            for trigger in triggers {
+                let t_lower = trigger.to_lowercase();
+                let evidence = scan.dependency_evidence(&t_lower);
+                let files = scan.extension_files(&t_lower);
+
+                if evidence.is_empty() && files.is_empty() {
+                    println!(
+                        "\n{} Gatilho '{}': não encontrado",
+                        style("✗").red(),
+                        trigger
+                    );
+                    continue;
+                }
+
+                trigger_matched = true;
+                println!(
+                    "\n{} Gatilho '{}': detectado",
+                    style("✔").green(),
+                    style(trigger).cyan()
+                );
+                for e in evidence {
+                    println!("   {} {}", style("↳").dim(), e);
+                }
+                for file in files {
+                    println!("   {} arquivo {}", style("↳").dim(), file.display());
+                }
+            }
+
+            // Ecossistema só conta quando nenhum gatilho bateu (mesma regra do audit)
+            if !trigger_matched {
+                if let Some((ext, eco)) = audit::ecosystem_of(&skill.id) {
+                    let files = scan.extension_files(ext);
+                    if files.is_empty() {
+                        println!(
+                            "\n{} Ecossistema {}: nenhum arquivo .{}",
+                            style("✗").red(),
+                            eco,
+                            ext
+                        );
+                    } else {
+                        println!(
+                            "\n{} Ecossistema {}: {} arquivos .{}",
+                            style("✔").green(),
+                            eco,
+                            files.len(),
+                            ext
+                        );
+                        for file in files {
+                            println!("   {} {}", style("↳").dim(), file.display());
+                        }
+                    }
+                }
+            }
+
+            match audit::evaluate(skill, &scan) {
+                Some(rec) => println!(
+                    "\n{} Recomendada com {}% de confiança: {}\n",
+                    style("🎯").green(),
+                    rec.confidence(),
+                    style(rec.reasons()).dim()
+                ),
+                None => println!(
+                    "\n{} Não recomendada: nenhuma evidência no projeto.\n",
+                    style("🚫").yellow()
+                ),
+            }
+        }
         Commands::Info { alias } => {
-            let registry = downloader::fetch_registry().await?;
+            let registry = downloader::fetch_registry(&session).await?;
             if let Some(skill) = registry.iter().find(|s| &s.id == alias) {
                 println!(
                     "\n{} Detalhes da Skill: {}",
@@ -455,6 +1010,18 @@ async fn main() -> anyhow::Result<()> {
                         "Grátis"
                     }
                 );
+                println!(
+                    "{} Assinatura: {}",
+                    style("🔏").green(),
+                    if session.keys_for(&skill.registry).is_empty() {
+                        "não exigida"
+                    } else {
+                        "obrigatória (minisign)"
+                    }
+                );
+                if let Some(sha) = &skill.sha256 {
+                    println!("{} SHA-256: {}", style("🧬").dim(), style(sha).dim());
+                }
                 println!(
                     "{} Endpoint: {}\n",
                     style("🔗").dim(),
@@ -465,21 +1032,275 @@ async fn main() -> anyhow::Result<()> {
             }
         }
 
-        Commands::Login { token } => {
+        Commands::Diff { alias } => {
+            let payload = downloader::fetch_skill(&session, alias).await?;
+            let file = match Lockfile::load(Path::new("."))?.get(alias) {
+                Some(locked) => locked.file.clone(),
+                None => installer::sanitize_file_name(&payload.file_name, &payload.name)?,
+            };
+            let path = Path::new(".cursor").join("rules").join(&file);
+            let installed = match fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    println!(
+                        "{} {} não está instalada; o diff mostra o arquivo inteiro.",
+                        style("ℹ").blue(),
+                        style(alias).cyan()
+                    );
+                    String::new()
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            let fresh = installer::render_managed(&payload);
+            if installed == fresh {
+                println!(
+                    "{} {} está idêntica ao conteúdo publicado.",
+                    style("✅").green(),
+                    style(alias).cyan()
+                );
+                return Ok(());
+            }
+            print_diff(&diff::unified_diff(
+                &installed,
+                &fresh,
+                &format!("a/{}", path.display()),
+                &format!("b/{}", path.display()),
+            ));
+        }
+
+        Commands::Lint {
+            paths,
+            json,
+            offline,
+        } => {
+            let mut report = lint::Report::default();
+            for path in paths {
+                lint::lint_path(path, &guard_config, &mut report)?;
+            }
+            if !*offline {
+                for remote in std::mem::take(&mut report.remote_urls) {
+                    if let Err(e) = downloader::probe(&session, &remote.url).await {
+                        report.push(
+                            &remote.file,
+                            Some(remote.location),
+                            lint::Severity::Error,
+                            "url-unreachable",
+                            format!("{} inacessível: {}", remote.url, e),
+                        );
+                    }
+                }
+            }
+
+            let errors = report.count(lint::Severity::Error);
+            let warnings = report.count(lint::Severity::Warning);
+            if *json {
+                let output = serde_json::json!({
+                    "files": report.files,
+                    "errors": errors,
+                    "warnings": warnings,
+                    "issues": report.issues,
+                });
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                for issue in &report.issues {
+                    let icon = match issue.severity {
+                        lint::Severity::Error => style("❌").red(),
+                        lint::Severity::Warning => style("⚠️").yellow(),
+                    };
+                    let location = issue
+                        .location
+                        .as_ref()
+                        .map(|l| format!(" ({})", l))
+                        .unwrap_or_default();
+                    println!(
+                        "{} {}{} {} {}",
+                        icon,
+                        style(&issue.file).bold(),
+                        location,
+                        style(format!("[{}]", issue.rule)).dim(),
+                        issue.message
+                    );
+                }
+                let summary = format!(
+                    "{} arquivo(s): {} erro(s), {} aviso(s)",
+                    report.files, errors, warnings
+                );
+                if errors == 0 {
+                    println!("{} {}", style("✅").green(), summary);
+                } else {
+                    println!("\n{} {}", style("🧪").red(), summary);
+                }
+            }
+            if errors > 0 {
+                std::process::exit(1);
+            }
+        }
+
+        Commands::Schema { kind } => {
+            let schema = match kind.as_str() {
+                "payload" => downloader::PAYLOAD_SCHEMA,
+                _ => downloader::REGISTRY_SCHEMA,
+            };
+            print!("{}", schema);
+        }
+
+        Commands::New { id, dir, premium } => {
+            let dir = match dir {
+                Some(dir) => dir.clone(),
+                None => {
+                    let (category, name) = authoring::parse_id(id)?;
+                    Path::new(".").join(category).join(name)
+                }
+            };
+            let files = authoring::scaffold(&dir, id, *premium)?;
+
+            println!(
+                "{} Skill '{}' criada em {}",
+                style("✨").green(),
+                style(id).cyan().bold(),
+                style(dir.display()).dim()
+            );
+            for file in files {
+                println!("   {} {}", style("↳").dim(), file.display());
+            }
+            println!(
+                "\n{} Edite {} e {}, depois teste com {}",
+                style("💡").yellow(),
+                authoring::SKILL_BODY,
+                authoring::SKILL_MANIFEST,
+                style(format!("rustskill add {}", SkillSource::local_arg(&dir))).green()
+            );
+        }
+
+        Commands::Scan { file } => {
+            let content = fs::read_to_string(file).map_err(|e| {
+                anyhow::anyhow!("❌ Não foi possível ler {}: {}", file.display(), e)
+            })?;
+            let findings = guard::inspect(&content, &guard_config);
+            if findings.is_empty() {
+                println!(
+                    "{} {}: nenhuma instrução suspeita encontrada.",
+                    style("✅").green(),
+                    file.display()
+                );
+                return Ok(());
+            }
+
+            println!(
+                "{} {}: {} achado(s)",
+                style("🛡️").yellow(),
+                file.display(),
+                findings.len()
+            );
+            for finding in &findings {
+                println!("   {} {}", style("↳").dim(), finding);
+            }
+            std::process::exit(1);
+        }
+
+        Commands::Login {
+            token,
+            with_token,
+            host,
+            device,
+        } => {
+            if *device {
+                let auth = oauth::start_device_flow(&session).await?;
+                println!(
+                    "{} Abra {} e digite o código: {}",
+                    style("🌐").cyan(),
+                    style(&auth.verification_uri).underlined(),
+                    style(&auth.user_code).bold().yellow()
+                );
+                if let Some(uri) = &auth.verification_uri_complete {
+                    println!("   {} Link direto: {}", style("↳").dim(), style(uri).dim());
+                }
+
+                let pb = ProgressBar::new_spinner();
+                pb.set_style(ProgressStyle::default_spinner().template("{spinner:.blue} {msg}")?);
+                pb.set_message("Aguardando autorização no navegador...");
+                pb.enable_steady_tick(Duration::from_millis(80));
+                let tokens = oauth::poll_for_token(&session, &auth).await;
+                pb.finish_and_clear();
+                let tokens = tokens?;
+
+                let location = oauth::store_tokens(&session.profile, &tokens)?;
+                println!(
+                    "{} Autenticação bem-sucedida! Acesso Premium liberado.",
+                    style("✅").green()
+                );
+                println!(
+                    "   {} Token guardado em: {}",
+                    style("↳").dim(),
+                    style(location).dim()
+                );
+                if let Some(account) =
+                    downloader::validate_token(&session, &tokens.access_token).await?
+                {
+                    print_account(&account);
+                }
+                return Ok(());
+            }
+
+            let token = match (token, token_file) {
+                (Some(token), _) => token.clone(),
+                (None, _) if *with_token => credentials::read_token_stdin()?,
+                (None, Some(path)) => credentials::read_token_file(path)?,
+                (None, None) => {
+                    println!(
+                        "{} Informe o token: {} ou {}",
+                        style("❌").red(),
+                        style("echo $TOKEN | rustskill login --with-token").green(),
+                        style("rustskill login --token-file <arquivo>").green()
+                    );
+                    return Ok(());
+                }
+            };
+
+            // Credencial de registry privado: só é enviada para este host
+            if let Some(host) = host {
+                let host = host.to_lowercase();
+                let location = credentials::store_token(
+                    &credentials::account(&session.profile, Some(&host)),
+                    &token,
+                )?;
+                let mut cfg = Config::load()?;
+                let profile = cfg.profiles.entry(session.profile.clone()).or_default();
+                if !profile.hosts.contains(&host) {
+                    profile.hosts.push(host.clone());
+                }
+                cfg.store()?;
+                println!(
+                    "{} Credencial de {} salva no perfil '{}' ({}).",
+                    style("✅").green(),
+                    style(&host).cyan(),
+                    session.profile,
+                    location
+                );
+                return Ok(());
+            }
+
             println!(
                 "{} Verificando credenciais de vanguarda...",
                 style("🔑").cyan()
             );
 
-            if downloader::validate_token(&token).await? {
-                let cfg = Config {
-                    token: Some(token.clone()),
-                };
-                confy::store("rustskill", None, cfg)?;
+            if let Some(account) = downloader::validate_token(&session, &token).await? {
+                let account_key = credentials::account(&session.profile, None);
+                let location = credentials::store_token(&account_key, &token)?;
+                // Um refresh token de um login --device anterior não pertence a este token
+                credentials::delete_token(&credentials::refresh_account(&account_key))?;
                 println!(
                     "{} Autenticação bem-sucedida! Acesso Premium liberado.",
                     style("✅").green()
                 );
+                println!(
+                    "   {} Token guardado em: {}",
+                    style("↳").dim(),
+                    style(location).dim()
+                );
+                print_account(&account);
             } else {
                 println!(
                     "{} Falha na autenticação. Verifique seu token em {}",
@@ -489,30 +1310,169 @@ async fn main() -> anyhow::Result<()> {
             }
         }
 
-        Commands::Upgrade => {
-            println!("{} Buscando vanguarda...", style("🔄").cyan());
-            let status = self_update::backends::github::Update::configure()
-                .repo_owner("cleitonaugusto")
-                .repo_name("rustskill")
-                .bin_name("rustskill")
-                .show_download_progress(true)
-                .current_version(env!("CARGO_PKG_VERSION"))
-                .build()?
-                .update()?;
-
-            if status.updated() {
+        Commands::Logout { host } => {
+            let account = credentials::account(&session.profile, host.as_deref());
+            if let Some(host) = host {
+                let mut cfg = Config::load()?;
+                if let Some(profile) = cfg.profiles.get_mut(&session.profile) {
+                    profile.hosts.retain(|h| !h.eq_ignore_ascii_case(host));
+                    cfg.store()?;
+                }
+            }
+
+            let refresh_removed = host.is_none()
+                && credentials::delete_token(&credentials::refresh_account(&account))?;
+            if credentials::delete_token(&account)? || refresh_removed {
                 println!(
-                    "{} Atualizado para {}!",
-                    style("✔").green(),
-                    status.version()
+                    "{} Credencial removida do perfil '{}'.",
+                    style("👋").cyan(),
+                    session.profile
                 );
             } else {
+                println!("{} Nenhum token guardado.", style("ℹ").blue());
+            }
+        }
+
+        Commands::Whoami => {
+            if session.token.is_none() {
                 println!(
-                    "{} Versão {} já é a mais recente.",
-                    style("✔").green(),
-                    env!("CARGO_PKG_VERSION")
+                    "{} Nenhum token guardado. Rode {} primeiro.",
+                    style("❌").red(),
+                    style("rustskill login --device").green()
                 );
+                return Ok(());
             }
+
+            match oauth::validate_with_refresh(&mut session).await? {
+                Some(account) => {
+                    println!(
+                        "{} Sessão premium (perfil '{}'):",
+                        style("🔑").cyan(),
+                        session.profile
+                    );
+                    print_account(&account);
+                }
+                None => println!(
+                    "{} Token recusado pela API (inválido ou expirado).",
+                    style("❌").red()
+                ),
+            }
+        }
+
+        Commands::Auth {
+            action: AuthAction::Status,
+        } => {
+            println!(
+                "{} Perfil: {} ({})",
+                style("👤").cyan(),
+                style(&session.profile).bold(),
+                session.api_base_url
+            );
+            let Some(source) = session.token_source.clone() else {
+                println!("{} Não autenticado.", style("🔒").yellow());
+                return Ok(());
+            };
+
+            println!("{} Token em uso: {}", style("🔐").cyan(), source);
+            match oauth::validate_with_refresh(&mut session).await {
+                Ok(Some(account)) if account.is_expired() => {
+                    println!("{} Status: expirado", style("❌").red())
+                }
+                Ok(Some(_)) => println!("{} Status: válido", style("✅").green()),
+                Ok(None) => println!("{} Status: recusado pela API", style("❌").red()),
+                Err(e) => println!(
+                    "{} Status: não verificado (API indisponível: {})",
+                    style("⚠️").yellow(),
+                    e
+                ),
+            }
+        }
+
+        Commands::Profile { .. } => unreachable!("perfis são tratados antes da sessão"),
+
+        Commands::Upgrade {
+            check,
+            version,
+            channel,
+            rollback,
+        } => {
+            let current = env!("CARGO_PKG_VERSION");
+            if *rollback {
+                updater::rollback()?;
+                println!(
+                    "{} Binário anterior restaurado (a versão {} ficou guardada para um novo rollback).",
+                    style("↩️").yellow(),
+                    current
+                );
+                return Ok(());
+            }
+
+            let channel = channel.unwrap_or(config.update.channel);
+            let source = config.update.source()?;
+            if source != updater::UpdateSource::default() {
+                println!("{} Origem das atualizações: {}", style("🏢").cyan(), source);
+            }
+
+            // A consulta de releases do self_update é bloqueante: roda fora das threads do runtime async
+            let release = match version {
+                Some(version) => {
+                    let version = version.clone();
+                    tokio::task::spawn_blocking(move || updater::find_release(&source, &version))
+                        .await??
+                }
+                None => {
+                    println!(
+                        "{} Buscando vanguarda (canal {})...",
+                        style("🔄").cyan(),
+                        channel
+                    );
+                    let latest = tokio::task::spawn_blocking(move || {
+                        updater::latest_release(&source, channel)
+                    })
+                    .await??;
+                    match latest {
+                        Some(release) if updater::is_newer(current, &release.version) => release,
+                        _ => {
+                            println!(
+                                "{} Versão {} já é a mais recente no canal {}.",
+                                style("✔").green(),
+                                current,
+                                channel
+                            );
+                            return Ok(());
+                        }
+                    }
+                }
+            };
+            let target = updater::normalize_version(&release.version);
+
+            if *check {
+                println!(
+                    "{} Atualização disponível: {} → {}",
+                    style("📦").cyan(),
+                    current,
+                    style(&target).green()
+                );
+                std::process::exit(10);
+            }
+            if target == current {
+                println!("{} Versão {} já instalada.", style("✔").green(), current);
+                return Ok(());
+            }
+
+            println!(
+                "{} Baixando {} e conferindo assinatura e checksum...",
+                style("🔏").cyan(),
+                style(&target).green()
+            );
+            updater::install_verified(&release, &config.update).await?;
+            println!("{} Atualizado para {}!", style("✔").green(), target);
+            println!(
+                "   {} Algo errado? {} volta para a {}.",
+                style("↳").dim(),
+                style("rustskill upgrade --rollback").green(),
+                current
+            );
         }
     }
     Ok(())
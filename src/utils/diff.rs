@@ -0,0 +1,10 @@
+use similar::TextDiff;
+
+/// Diff unificado (3 linhas de contexto) entre o conteúdo atual e o proposto
+pub fn unified_diff(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    TextDiff::from_lines(old, new)
+        .unified_diff()
+        .context_radius(3)
+        .header(old_label, new_label)
+        .to_string()
+}
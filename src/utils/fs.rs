@@ -0,0 +1,30 @@
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+/// Grava em um arquivo temporário no mesmo diretório e renomeia: o alvo nunca fica pela metade
+pub fn write_atomic(path: &Path, content: &[u8]) -> anyhow::Result<()> {
+    let dir = path
+        .parent()
+        .ok_or_else(|| anyhow::anyhow!("❌ Caminho sem diretório: {}", path.display()))?;
+    let name = path
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("❌ Caminho sem nome de arquivo: {}", path.display()))?;
+    let tmp = dir.join(format!(
+        ".{}.{}.tmp",
+        name.to_string_lossy(),
+        std::process::id()
+    ));
+
+    let result = (|| -> anyhow::Result<()> {
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(content)?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    })();
+    result.map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        anyhow::anyhow!("❌ Falha ao gravar {}: {}", path.display(), e)
+    })
+}
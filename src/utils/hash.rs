@@ -0,0 +1,6 @@
+use sha2::{Digest, Sha256};
+
+/// SHA-256 em hexadecimal minúsculo (formato usado no lockfile)
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
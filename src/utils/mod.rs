@@ -1 +1,3 @@
-
+pub mod diff;
+pub mod fs;
+pub mod hash;